@@ -1,16 +1,51 @@
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{quote, ToTokens};
+use syn::ext::IdentExt;
 
 /// Derive macro for implementing `FrcStructure` on a struct.
-#[proc_macro_derive(FrcStructure)]
+///
+/// Fieldless enums with a `#[repr(u8/i8/.../i64)]` are packed as their discriminant,
+/// fields of them are declared inline with every variant (`enum {A=0, B=1} uint8 mode`)
+/// and values that don't match a variant fail to unpack
+///
+/// Integer and bool fields can be bit-fields with `#[frc(bits = N)]`,
+/// consecutive bit-fields are packed into shared storage units like the schema parser expects
+///
+/// Array lengths can be any const expression, multi-dimensional arrays
+/// are flattened in row-major order (`[[f64; 3]; 3]` is `float64 name[9]`)
+///
+/// Tuple struct fields are named `_0`, `_1`... in the schema.
+/// Generic structs require their type params to be `FrcStructure`s,
+/// each instantiation has its own type string (`Pair<Meter>` is `Pair_Meter`, `Samples<4>` is `Samples_4`)
+/// and is registered by the non-generic structures holding it, otherwise with `FrcStructDescDB::add_ref`
+///
+/// Unit newtype fields marked `#[frc(unit)]` are packed as their inner `f64`,
+/// `#[frc(unit = "u64")]` picks another inner primitive (like for `Microsecond`)
+///
+/// Other attributes:
+/// - `#[frc(rename = "...")]` on the type replaces the type string, on a field the schema name
+/// - `#[frc(skip)]` leaves a field out of the schema, it unpacks to its `Default`
+/// - `#[frc(type = "float32")]` casts a number field to another schema type on the wire
+/// - `#[frc(schema_only)]` skips a nested struct field but still registers its description
+#[proc_macro_derive(FrcStructure, attributes(frc))]
 pub fn frc_structure(input: TokenStream) -> TokenStream {
-    let ast = syn::parse(input).unwrap();
-    impl_frc_struct(&ast).into()
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+    let expanded = match &ast.data {
+        syn::Data::Enum(data) => impl_frc_enum(&ast, data),
+        syn::Data::Struct(data) => impl_frc_struct(&ast, data),
+        syn::Data::Union(data) => Err(syn::Error::new(
+            data.union_token.span,
+            "FrcStructure can't be derived for unions",
+        )),
+    };
+    expanded
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
-fn schema_type_name(name: &syn::Ident) -> String {
-    match name.to_string().as_str() {
+fn schema_type_name(name: &str) -> String {
+    match name {
         "f64" => "float64",
         "f32" => "float32",
         "i64" => "int64",
@@ -28,217 +63,817 @@ fn schema_type_name(name: &syn::Ident) -> String {
     .to_owned()
 }
 
-fn type_size(path: &syn::Path) -> String {
-    let segment = path.segments.last().unwrap();
-    let segment_name = &segment.ident;
-    match segment_name.to_string().as_str() {
-        "f64" => "8",
-        "f32" => "4",
-        "i64" => "8",
-        "i32" => "4",
-        "i16" => "2",
-        "i8" => "1",
-        "u64" => "8",
-        "u32" => "4",
-        "u16" => "2",
-        "u8" => "1",
-        "bool" => "1",
-        "char" => "1",
-        _ => {
-            return format!(
-                "<{:} as frc_values::structure::FrcStructure>::SIZE",
-                path.into_token_stream()
-            )
+fn prim_size(name: &str) -> Option<usize> {
+    match name {
+        "f64" => Some(8),
+        "f32" => Some(4),
+        "i64" => Some(8),
+        "i32" => Some(4),
+        "i16" => Some(2),
+        "i8" => Some(1),
+        "u64" => Some(8),
+        "u32" => Some(4),
+        "u16" => Some(2),
+        "u8" => Some(1),
+        "bool" => Some(1),
+        "char" => Some(1),
+        _ => None,
+    }
+}
+
+fn is_number(name: &str) -> bool {
+    !matches!(name, "bool" | "char") && prim_size(name).is_some()
+}
+
+/// The rust primitive of a schema number type
+fn rust_prim(type_name: &str) -> Option<&'static str> {
+    match type_name {
+        "float" | "float32" => Some("f32"),
+        "double" | "float64" => Some("f64"),
+        "int8" => Some("i8"),
+        "int16" => Some("i16"),
+        "int32" => Some("i32"),
+        "int64" => Some("i64"),
+        "uint8" => Some("u8"),
+        "uint16" => Some("u16"),
+        "uint32" => Some("u32"),
+        "uint64" => Some("u64"),
+        _ => None,
+    }
+}
+
+/// How a field type is put in the buffer
+enum FieldKind {
+    /// A primitive packed as itself
+    Prim(String),
+    /// A `frc-units` style newtype packed as its inner primitive,
+    /// converted with `From<Unit> for inner` and `From<f64> for Unit`
+    Unit(String, syn::Path),
+    /// A nested `FrcStructure`
+    Struct(syn::Path),
+    /// A number primitive cast to another on the wire, `(wire, field)`
+    Cast(String, String),
+}
+
+impl FieldKind {
+    fn new(path: &syn::Path, attrs: &FieldAttrs) -> syn::Result<Self> {
+        let segment_name = path.segments.last().unwrap().ident.to_string();
+        if let Some(inner) = &attrs.unit {
+            return Ok(Self::Unit(inner.clone(), path.clone()));
+        }
+        if let Some(wire) = &attrs.wire_type {
+            if !is_number(&segment_name) {
+                return Err(syn::Error::new_spanned(
+                    path,
+                    "only number fields can be cast to another type",
+                ));
+            }
+            return Ok(Self::Cast(wire.clone(), segment_name));
+        }
+        if prim_size(&segment_name).is_some() {
+            Ok(Self::Prim(segment_name))
+        } else {
+            Ok(Self::Struct(path.clone()))
         }
     }
-    .to_owned()
 }
 
-fn type_pack(path: &syn::Path, field: &syn::Ident) -> String {
-    let segment = path.segments.last().unwrap();
-    let segment_name = &segment.ident;
-    match segment_name.to_string().as_str() {
-        "f64" => format!("buffer.put_f64_le(self.{});", field),
-        "f32" => format!("buffer.put_f32_le(self.{});", field),
-        "i64" => format!("buffer.put_i64_le(self.{});", field),
-        "i32" => format!("buffer.put_i32_le(self.{});", field),
-        "i16" => format!("buffer.put_i16_le(self.{});", field),
-        "i8" => format!("buffer.put_i8(self.{});", field),
-        "u64" => format!("buffer.put_u64_le(self.{});", field),
-        "u32" => format!("buffer.put_u32_le(self.{});", field),
-        "u16" => format!("buffer.put_u16_le(self.{});", field),
-        "u8" => format!("buffer.put_u8(self.{});", field),
-        "bool" => format!("buffer.put_u8(if self.{} {{ 1 }} else {{ 0 }});", field),
-        "char" => format!("buffer.put_u8(self.{} as u8);", field),
-        _ => {
-            return format!(
-                "frc_values::structure::FrcStructure::pack(&self.{:}, buffer);",
-                field
-            )
+fn type_size(kind: &FieldKind) -> String {
+    match kind {
+        FieldKind::Prim(prim) | FieldKind::Unit(prim, _) | FieldKind::Cast(prim, _) => {
+            prim_size(prim).unwrap().to_string()
         }
+        FieldKind::Struct(path) => format!(
+            "<{:} as frc_values::structure::FrcStructure>::SIZE",
+            path.into_token_stream()
+        ),
+    }
+}
+
+fn prim_pack(prim: &str, value: &str) -> String {
+    match prim {
+        "f64" => format!("buffer.put_f64_le({});", value),
+        "f32" => format!("buffer.put_f32_le({});", value),
+        "i64" => format!("buffer.put_i64_le({});", value),
+        "i32" => format!("buffer.put_i32_le({});", value),
+        "i16" => format!("buffer.put_i16_le({});", value),
+        "i8" => format!("buffer.put_i8({});", value),
+        "u64" => format!("buffer.put_u64_le({});", value),
+        "u32" => format!("buffer.put_u32_le({});", value),
+        "u16" => format!("buffer.put_u16_le({});", value),
+        "u8" => format!("buffer.put_u8({});", value),
+        "bool" => format!("buffer.put_u8(if {} {{ 1 }} else {{ 0 }});", value),
+        "char" => format!("buffer.put_u8({} as u8);", value),
+        _ => unreachable!(),
     }
 }
 
-fn array_type_pack(path: &syn::Path, field: &syn::Ident, len: usize) -> String {
-    let segment = path.segments.last().unwrap();
-    let segment_name = &segment.ident;
-    let elem_pack = match segment_name.to_string().as_str() {
-        "f64" => format!("buffer.put_f64_le(self.{}[i]);", field),
-        "f32" => format!("buffer.put_f32_le(self.{}[i]);", field),
-        "i64" => format!("buffer.put_i64_le(self.{}[i]);", field),
-        "i32" => format!("buffer.put_i32_le(self.{}[i]);", field),
-        "i16" => format!("buffer.put_i16_le(self.{}[i]);", field),
-        "i8" => format!("buffer.put_i8(self.{}[i]);", field),
-        "u64" => format!("buffer.put_u64_le(self.{}[i]);", field),
-        "u32" => format!("buffer.put_u32_le(self.{}[i]);", field),
-        "u16" => format!("buffer.put_u16_le(self.{}[i]);", field),
-        "u8" => format!("buffer.put_u8(self.{}[i]);", field),
-        "bool" => format!("buffer.put_u8(if self.{}[i] {{ 1 }} else {{ 0 }});", field),
-        "char" => format!("buffer.put_u8(self.{}[i] as u8);", field),
-        _ => format!(
-            "frc_values::structure::FrcStructure::pack(&self.{:}[i], buffer);",
-            field
+fn prim_unpack(prim: &str) -> String {
+    match prim {
+        "f64" => "buffer.get_f64_le()",
+        "f32" => "buffer.get_f32_le()",
+        "i64" => "buffer.get_i64_le()",
+        "i32" => "buffer.get_i32_le()",
+        "i16" => "buffer.get_i16_le()",
+        "i8" => "buffer.get_i8()",
+        "u64" => "buffer.get_u64_le()",
+        "u32" => "buffer.get_u32_le()",
+        "u16" => "buffer.get_u16_le()",
+        "u8" => "buffer.get_u8()",
+        "bool" => "buffer.get_u8() != 0",
+        "char" => "buffer.get_u8() as char",
+        _ => unreachable!(),
+    }
+    .to_owned()
+}
+
+fn type_pack(kind: &FieldKind, value: &str) -> String {
+    match kind {
+        FieldKind::Prim(prim) => prim_pack(prim, value),
+        FieldKind::Unit(prim, _) => prim_pack(prim, &format!("{}::from({})", prim, value)),
+        FieldKind::Cast(wire, _) => prim_pack(wire, &format!("{} as {}", value, wire)),
+        FieldKind::Struct(_) => format!(
+            "frc_values::structure::FrcStructure::pack(&{}, buffer);",
+            value
         ),
-    };
-    format!("for i in 0..{} {{ {} }}", len, elem_pack)
+    }
 }
 
-fn type_unpack(path: &syn::Path, field: &syn::Ident) -> String {
-    let segment = path.segments.last().unwrap();
-    let segment_name = &segment.ident;
-    match segment_name.to_string().as_str() {
-        "f64" => format!("{}: buffer.get_f64_le()", field),
-        "f32" => format!("{}: buffer.get_f32_le()", field),
-        "i64" => format!("{}: buffer.get_i64_le()", field),
-        "i32" => format!("{}: buffer.get_i32_le()", field),
-        "i16" => format!("{}: buffer.get_i16_le()", field),
-        "i8" => format!("{}: buffer.get_i8()", field),
-        "u64" => format!("{}: buffer.get_u64_le()", field),
-        "u32" => format!("{}: buffer.get_u32_le()", field),
-        "u16" => format!("{}: buffer.get_u16_le()", field),
-        "u8" => format!("{}: buffer.get_u8()", field),
-        "bool" => format!("{}: buffer.get_u8() != 0", field),
-        "char" => format!("{}: buffer.get_u8() as char", field),
-        _ => {
-            return format!(
-                "{}: <{:} as frc_values::structure::FrcStructure>::unpack(buffer)",
-                field,
-                path.into_token_stream()
+/// Packs every element of a (multi-dimensional) array in row-major order,
+/// `lens` are the lengths of the dimensions from the outermost
+fn array_type_pack(kind: &FieldKind, value: &str, lens: &[String]) -> String {
+    let indices = (0..lens.len())
+        .map(|i| format!("[i{}]", i))
+        .collect::<String>();
+    let mut pack = type_pack(kind, &format!("{}{}", value, indices));
+    for (i, len) in lens.iter().enumerate().rev() {
+        pack = format!("for i{} in 0..{} {{ {} }}", i, len, pack);
+    }
+    pack
+}
+
+fn type_unpack(kind: &FieldKind) -> String {
+    match kind {
+        FieldKind::Prim(prim) => prim_unpack(prim),
+        FieldKind::Unit(prim, path) if prim == "f64" => {
+            format!(
+                "<{}>::from({})",
+                path.into_token_stream(),
+                prim_unpack(prim)
             )
         }
+        FieldKind::Unit(prim, path) => format!(
+            "<{}>::from({} as f64)",
+            path.into_token_stream(),
+            prim_unpack(prim)
+        ),
+        FieldKind::Struct(path) => format!(
+            "<{:} as frc_values::structure::FrcStructure>::unpack(buffer)",
+            path.into_token_stream()
+        ),
+        FieldKind::Cast(wire, prim) => format!("{} as {}", prim_unpack(wire), prim),
     }
 }
 
-fn array_type_unpack(path: &syn::Path, field: &syn::Ident, len: usize) -> String {
-    let segment = path.segments.last().unwrap();
-    let segment_name = &segment.ident;
-    let elem_unpack = match segment_name.to_string().as_str() {
-        "f64" => format!("buffer.get_f64_le()"),
-        "f32" => format!("buffer.get_f32_le()"),
-        "i64" => format!("buffer.get_i64_le()"),
-        "i32" => format!("buffer.get_i32_le()"),
-        "i16" => format!("buffer.get_i16_le()"),
-        "i8" => format!("buffer.get_i8()"),
-        "u64" => format!("buffer.get_u64_le()"),
-        "u32" => format!("buffer.get_u32_le()"),
-        "u16" => format!("buffer.get_u16_le()"),
-        "u8" => format!("buffer.get_u8()"),
-        "bool" => format!("buffer.get_u8() != 0"),
-        "char" => format!("buffer.get_u8() as char"),
-        _ => return format!("{}: [{}]",field, format!(
-            "<{:} as frc_values::structure::FrcStructure>::unpack(buffer), ",
-            path.into_token_stream()
-        ).repeat(len))
-    };
-    let zero_val = match segment_name.to_string().as_str() {
-        "f64" => "0.0f64",
-        "f32" => "0.0f32",
-        "i64" => "0i64",
-        "i32" => "0i32",
-        "i16" => "0i16",
-        "i8" => "0i8",
-        "u64" => "0u64",
-        "u32" => "0u32",
-        "u16" => "0u16",
-        "u8" => "0u8",
-        "bool" => "false",
-        "char" => "'\\0'",
-        _ => unreachable!(),
+/// Unpacks the elements of a (multi-dimensional) array with `depth` dimensions,
+/// the lengths are inferred from the field type
+fn array_type_unpack(kind: &FieldKind, depth: usize) -> String {
+    let mut unpack = type_unpack(kind);
+    for _ in 0..depth {
+        unpack = format!("core::array::from_fn(|_| {})", unpack);
+    }
+    unpack
+}
+
+fn enum_discriminant(expr: &syn::Expr) -> syn::Result<i64> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(int),
+            ..
+        }) => int.base10_parse::<i64>(),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => Ok(-enum_discriminant(expr)?),
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            "only literal enum discriminants are supported",
+        )),
+    }
+}
+
+fn impl_frc_enum(ast: &syn::DeriveInput, data: &syn::DataEnum) -> syn::Result<TokenStream2> {
+    let name = &ast.ident;
+    let repr_error = "enums need a #[repr(u8/i8/u16/i16/u32/i32/u64/i64)] attribute";
+    let Some(repr_attr) = ast.attrs.iter().find(|attr| attr.path().is_ident("repr")) else {
+        return Err(syn::Error::new_spanned(name, repr_error));
     };
-    format!(
-        "{}: {{ let mut {} = [{}; {}]; for i in 0..{} {{ {}[i] = {} }}; {} }}",
-        field, field, zero_val, len, len, field, elem_unpack, field
-    )
+    let repr = repr_attr
+        .parse_args::<syn::Ident>()
+        .map_err(|err| syn::Error::new(err.span(), repr_error))?;
+    if !matches!(
+        repr.to_string().as_str(),
+        "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "u64" | "i64"
+    ) {
+        return Err(syn::Error::new_spanned(repr, repr_error));
+    }
+
+    let mut discriminant = 0i64;
+    let mut variants = Vec::new();
+    for variant in &data.variants {
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                &variant.fields,
+                "only fieldless enums are supported",
+            ));
+        }
+        if let Some((_, expr)) = &variant.discriminant {
+            discriminant = enum_discriminant(expr)?;
+        }
+        variants.push((&variant.ident, discriminant));
+        discriminant += 1;
+    }
+    if variants.is_empty() {
+        return Err(syn::Error::new_spanned(
+            name,
+            "enums need at least one variant",
+        ));
+    }
+
+    // fields of the enum are declared inline, standalone values are a struct of one field
+    let spec = format!(
+        "enum {{{}}} {}",
+        variants
+            .iter()
+            .map(|(ident, value)| format!("{}={}", ident.unraw(), value))
+            .collect::<Vec<_>>()
+            .join(", "),
+        schema_type_name(&repr.to_string())
+    );
+    let schema = format!("{} value", spec);
+    let kind = FieldKind::Prim(repr.to_string());
+    let size_expr = syn::parse_str::<syn::Expr>(&type_size(&kind))?;
+    let pack = syn::parse_str::<syn::Stmt>(&type_pack(&kind, "value"))?;
+    let unpack = syn::parse_str::<syn::Expr>(&type_unpack(&kind))?;
+    let type_str = type_attrs(ast)?.rename.unwrap_or_else(|| name.to_string());
+    let idents = variants.iter().map(|(ident, _)| ident).collect::<Vec<_>>();
+    let values = variants
+        .iter()
+        .map(|(_, value)| syn::parse_str::<syn::Expr>(&format!("{}{}", value, repr)))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        impl frc_values::structure::FrcStructure for #name {
+            const SIZE: usize = #size_expr;
+            const SCHEMA: &'static str = #schema;
+            const TYPE: &'static str = #type_str;
+            const DESCRIPTION: frc_values::structure::FrcStructDesc = frc_values::structure::FrcStructDesc {
+                schema: Self::SCHEMA,
+                type_str: Self::TYPE,
+                size: Self::SIZE,
+            };
+            const ENUM_SPEC: Option<&'static str> = Some(#spec);
+
+            fn pack(&self, buffer: &mut impl frc_values::bytes::BufMut) {
+                let value: #repr = match self {
+                    #(Self::#idents => #values),*
+                };
+                #pack
+            }
+
+            /// Panics on values that don't match any variant
+            fn unpack(buffer: &mut impl frc_values::bytes::Buf) -> Self {
+                let value: #repr = #unpack;
+                #[allow(unreachable_patterns)]
+                match value {
+                    #(#values => Self::#idents,)*
+                    _ => panic!("{} is not a {} variant", value, Self::TYPE),
+                }
+            }
+
+            fn is_valid(mut buffer: &[u8]) -> bool {
+                if buffer.len() < Self::SIZE {
+                    return false;
+                }
+                let buffer = &mut buffer;
+                let value: #repr = #unpack;
+                matches!(value, #(#values)|*)
+            }
+        }
+    })
+}
+
+/// The `#[frc(...)]` attributes of a field
+#[derive(Default)]
+struct FieldAttrs {
+    bits: Option<u32>,
+    unit: Option<String>,
+    rename: Option<String>,
+    skip: bool,
+    /// The rust primitive of the `type = "..."` schema type
+    wire_type: Option<String>,
+    schema_only: bool,
+}
+
+fn field_attrs(field: &syn::Field) -> syn::Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("frc"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("bits") {
+                let bits: syn::LitInt = meta.value()?.parse()?;
+                attrs.bits = Some(bits.base10_parse()?);
+                Ok(())
+            } else if meta.path.is_ident("unit") {
+                let inner = if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<syn::LitStr>()?.value()
+                } else {
+                    "f64".to_owned()
+                };
+                if !is_number(&inner) {
+                    return Err(meta.error("units are packed as a primitive number type"));
+                }
+                attrs.unit = Some(inner);
+                Ok(())
+            } else if meta.path.is_ident("rename") {
+                attrs.rename = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                attrs.skip = true;
+                Ok(())
+            } else if meta.path.is_ident("type") {
+                let type_name = meta.value()?.parse::<syn::LitStr>()?.value();
+                let Some(prim) = rust_prim(&type_name) else {
+                    return Err(meta.error("fields can only be cast to schema number types"));
+                };
+                attrs.wire_type = Some(prim.to_owned());
+                Ok(())
+            } else if meta.path.is_ident("schema_only") {
+                attrs.schema_only = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown frc attribute"))
+            }
+        })?;
+    }
+    Ok(attrs)
+}
+
+/// The `#[frc(...)]` attributes of a struct or enum
+#[derive(Default)]
+struct TypeAttrs {
+    rename: Option<String>,
+}
+
+fn type_attrs(ast: &syn::DeriveInput) -> syn::Result<TypeAttrs> {
+    let mut attrs = TypeAttrs::default();
+    for attr in ast.attrs.iter().filter(|attr| attr.path().is_ident("frc")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                attrs.rename = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("unknown frc attribute"))
+            }
+        })?;
+    }
+    Ok(attrs)
+}
+
+/// The size in bytes and signedness of a type that can be a bit-field
+fn bitfield_type(name: &str) -> Option<(usize, bool)> {
+    match name {
+        "bool" | "u8" => Some((1, false)),
+        "i8" => Some((1, true)),
+        "u16" => Some((2, false)),
+        "i16" => Some((2, true)),
+        "u32" => Some((4, false)),
+        "i32" => Some((4, true)),
+        "u64" => Some((8, false)),
+        "i64" => Some((8, true)),
+        _ => None,
+    }
+}
+
+/// The storage unit consecutive bit-fields are packed into
+struct BitfieldUnit {
+    index: usize,
+    storage: usize,
+    used: u32,
+    packing: Vec<String>,
 }
 
-fn impl_frc_struct(ast: &syn::DeriveInput) -> TokenStream2 {
+impl BitfieldUnit {
+    fn pack(&self) -> String {
+        format!(
+            "{{ let mut unit: u64 = 0; {} buffer.put_uint_le(unit, {}); }}",
+            self.packing.join(" "),
+            self.storage
+        )
+    }
+}
+
+/// A line of the generated `unpack`, fields are read into locals first
+/// so bit-fields can share the read of their storage unit
+enum Unpack {
+    Stmt(String),
+    Field(String, String),
+}
+
+/// A piece of a generated string constant,
+/// nested structs are only known by their `FrcStructure::TYPE` at compile time
+enum Part {
+    Lit(String),
+    /// A `&str` expression evaluated at compile time
+    Expr(TokenStream2),
+}
+
+/// The capacity of strings concatenated in generic impls,
+/// their length can't depend on the generic params
+const GENERIC_CAPACITY: usize = 1024;
+
+#[derive(Default)]
+struct Parts(Vec<Part>);
+
+impl Parts {
+    fn push_str(&mut self, string: &str) {
+        if let Some(Part::Lit(last)) = self.0.last_mut() {
+            last.push_str(string);
+        } else {
+            self.0.push(Part::Lit(string.to_owned()));
+        }
+    }
+
+    fn push_type(&mut self, ty: TokenStream2) {
+        self.0.push(Part::Expr(
+            quote! { <#ty as frc_values::structure::FrcStructure>::TYPE },
+        ));
+    }
+
+    /// The decimal digits of a `usize` expression
+    fn push_number(&mut self, number: TokenStream2) {
+        self.0.push(Part::Expr(quote! {
+            frc_values::structure::__derive::ConstStr::<20>::number(#number).as_str()
+        }));
+    }
+
+    /// The schema type name of the field kind
+    fn push_kind(&mut self, kind: &FieldKind) {
+        match kind {
+            FieldKind::Prim(prim) | FieldKind::Unit(prim, _) | FieldKind::Cast(prim, _) => {
+                self.push_str(&schema_type_name(prim))
+            }
+            FieldKind::Struct(path) => self.0.push(Part::Expr(quote! {
+                frc_values::structure::__derive::field_type::<#path>()
+            })),
+        }
+    }
+
+    /// Starts a new schema declaration
+    fn declare(&mut self, kind: &FieldKind, declaration: &str) {
+        if !self.0.is_empty() {
+            self.push_str("; ");
+        }
+        self.push_kind(kind);
+        self.push_str(" ");
+        self.push_str(declaration);
+    }
+
+    /// A `&'static str` expression of the parts, and the hidden constant
+    /// concatenating them if they aren't a single literal
+    fn to_str(&self, constant: &str, generic: bool) -> (TokenStream2, TokenStream2) {
+        match self.0.as_slice() {
+            [] => return (quote! { "" }, quote! {}),
+            [Part::Lit(string)] => return (quote! { #string }, quote! {}),
+            _ => {}
+        }
+        let parts = self.0.iter().map(|part| match part {
+            Part::Lit(string) => quote! { #string },
+            Part::Expr(expr) => expr.clone(),
+        });
+        let parts = quote! { &[#(#parts),*] };
+        let capacity = if generic {
+            quote! { #GENERIC_CAPACITY }
+        } else {
+            quote! { { frc_values::structure::__derive::concat_len(#parts) } }
+        };
+        let constant = quote::format_ident!("{}", constant);
+        (
+            quote! { Self::#constant.as_str() },
+            quote! {
+                #[doc(hidden)]
+                const #constant: frc_values::structure::__derive::ConstStr<#capacity> =
+                    frc_values::structure::__derive::ConstStr::concat(#parts);
+            },
+        )
+    }
+}
+
+fn impl_frc_struct(ast: &syn::DeriveInput, data: &syn::DataStruct) -> syn::Result<TokenStream2> {
     let name = &ast.ident;
-    let mut schema = String::new();
+    let mut schema = Parts::default();
     let mut size_expr = String::new();
     let mut packing = Vec::new();
     let mut unpacking = Vec::new();
-    if let syn::Data::Struct(syn::DataStruct {
-        fields: syn::Fields::Named(syn::FieldsNamed { named: fields, .. }),
-        ..
-    }) = &ast.data
-    {
-        for field in fields {
-            let field_name = field.ident.as_ref().unwrap();
-            let field_type = &field.ty;
-            match field_type {
-                syn::Type::Path(syn::TypePath { path, .. }) => {
-                    let segment = path.segments.last().unwrap();
-                    let segment_name = &segment.ident;
-                    let type_name = schema_type_name(segment_name);
-                    schema.push_str(format!(" {} {};", type_name, field_name).as_str());
-                    size_expr.push_str(format!("{} + ", type_size(path)).as_str());
-                    packing.push(type_pack(path, field_name));
-                    unpacking.push(type_unpack(path, field_name));
+    let mut unit: Option<BitfieldUnit> = None;
+    let mut units = 0;
+    let mut schema_only = Vec::new();
+    let mut nested = Vec::new();
+    // nested structures (and enums) check their own bytes
+    let mut validation = Vec::new();
+    if data.fields.is_empty() {
+        return Err(syn::Error::new_spanned(
+            name,
+            "FrcStructure can't be derived for structs without fields",
+        ));
+    }
+    for (index, field) in data.fields.iter().enumerate() {
+        // tuple struct fields are declared as `_0`, `_1`... in the schema,
+        // raw identifiers (`r#type`) are declared without the prefix
+        let (member, schema_name) = match &field.ident {
+            Some(ident) => (ident.to_string(), ident.unraw().to_string()),
+            None => (index.to_string(), format!("_{}", index)),
+        };
+        let field_type = &field.ty;
+        let attrs = field_attrs(field)?;
+        let schema_name = attrs.rename.clone().unwrap_or(schema_name);
+        // skipped fields aren't in the schema or the buffer and unpack to their default,
+        // the descriptions of schema only fields are still registered
+        if attrs.skip || attrs.schema_only {
+            if attrs.schema_only {
+                // generic structures can only be registered once instantiated
+                if !ast.generics.params.is_empty() {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "schema only fields can't be registered from generic structs",
+                    ));
                 }
-                syn::Type::Array(syn::TypeArray { elem, len, .. }) => match elem.as_ref() {
-                    syn::Type::Path(syn::TypePath { path, .. }) => {
-                        let segment = path.segments.last().unwrap();
-                        let segment_name = &segment.ident;
-                        let type_name = schema_type_name(segment_name);
-                        if let syn::Expr::Lit(syn::ExprLit {
-                            lit: syn::Lit::Int(len_lit),
-                            ..
-                        }) = len
-                        {
-                            let len = len_lit.base10_parse::<usize>().unwrap();
-                            schema.push_str(
-                                format!(" {} {}[{}];", type_name, field_name, len).as_str(),
-                            );
-                            size_expr
-                                .push_str(format!("({} * {}) + ", type_size(path), len).as_str());
-                            packing.push(array_type_pack(path, field_name, len));
-                            unpacking.push(array_type_unpack(path, field_name, len));
-                        } else {
-                            panic!("Only arrays with literal lengths are supported");
-                        }
-                    }
-                    _ => panic!("Invalid array type"),
+                schema_only.push(field_type);
+            }
+            unpacking.push(Unpack::Field(
+                member,
+                "core::default::Default::default()".to_owned(),
+            ));
+            continue;
+        }
+        if let Some(width) = attrs.bits {
+            let bitfield_error = "only integer and bool fields can be bit-fields";
+            let syn::Type::Path(syn::TypePath { path, .. }) = field_type else {
+                return Err(syn::Error::new_spanned(field_type, bitfield_error));
+            };
+            let segment_name = path.segments.last().unwrap().ident.to_string();
+            let Some((storage, signed)) = bitfield_type(&segment_name) else {
+                return Err(syn::Error::new_spanned(field_type, bitfield_error));
+            };
+            let is_bool = segment_name == "bool";
+            if width == 0 || width > storage as u32 * 8 || is_bool && width != 1 {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    format!("invalid bit-field width {} for {}", width, segment_name),
+                ));
+            }
+            let fits = unit.as_ref().is_some_and(|unit| {
+                (unit.storage == storage || is_bool) && unit.used + width <= unit.storage as u32 * 8
+            });
+            if !fits {
+                if let Some(unit) = unit.take() {
+                    packing.push(unit.pack());
+                }
+                unpacking.push(Unpack::Stmt(format!(
+                    "let __frc_unit_{} = buffer.get_uint_le({});",
+                    units, storage
+                )));
+                size_expr.push_str(format!("{} + ", storage).as_str());
+                unit = Some(BitfieldUnit {
+                    index: units,
+                    storage,
+                    used: 0,
+                    packing: Vec::new(),
+                });
+                units += 1;
+            }
+            let current = unit.as_mut().unwrap();
+            let shift = current.used;
+            let mask = u64::MAX >> (64 - width);
+            current.used += width;
+            current.packing.push(format!(
+                "unit |= ((self.{} as u64) & {}u64) << {};",
+                member, mask, shift
+            ));
+            let raw = format!(
+                "((__frc_unit_{} >> {}) & {}u64)",
+                current.index, shift, mask
+            );
+            unpacking.push(Unpack::Field(
+                member,
+                if is_bool {
+                    format!("{} != 0", raw)
+                } else if signed {
+                    format!(
+                        "(({} << {}) as i64 >> {}) as {}",
+                        raw,
+                        64 - width,
+                        64 - width,
+                        segment_name
+                    )
+                } else {
+                    format!("{} as {}", raw, segment_name)
                 },
-                _ => panic!("Only structs/prims and arrays of structs/prims are supported"),
+            ));
+            schema.declare(
+                &FieldKind::Prim(segment_name),
+                &format!("{}:{}", schema_name, width),
+            );
+            continue;
+        }
+        if let Some(unit) = unit.take() {
+            packing.push(unit.pack());
+        }
+        let value = format!("self.{}", member);
+        // multi-dimensional arrays are flattened into a single schema array
+        let mut elem = field_type;
+        let mut lens = Vec::new();
+        while let syn::Type::Array(array) = elem {
+            lens.push(&array.len);
+            elem = &array.elem;
+        }
+        let syn::Type::Path(syn::TypePath { path, .. }) = elem else {
+            let message = if lens.is_empty() {
+                "only structures, primitives and arrays of them are supported"
+            } else {
+                "only arrays of structures and primitives are supported"
+            };
+            return Err(syn::Error::new_spanned(elem, message));
+        };
+        let kind = FieldKind::new(path, &attrs)?;
+        if let FieldKind::Struct(path) = &kind {
+            nested.push(path.clone());
+            let path = path.into_token_stream();
+            let offset = format!("{} 0", size_expr);
+            validation.push(if lens.is_empty() {
+                format!(
+                    "<{} as frc_values::structure::FrcStructure>::is_valid(&buffer[{}..])",
+                    path, offset
+                )
+            } else {
+                format!(
+                    "(0..{}).all(|i| <{} as frc_values::structure::FrcStructure>::is_valid(&buffer[{} + i * <{} as frc_values::structure::FrcStructure>::SIZE..]))",
+                    lens.iter()
+                        .map(|len| format!("({})", len.into_token_stream()))
+                        .collect::<Vec<_>>()
+                        .join(" * "),
+                    path,
+                    offset,
+                    path
+                )
+            });
+        }
+        if lens.is_empty() {
+            schema.declare(&kind, &schema_name);
+            size_expr.push_str(format!("{} + ", type_size(&kind)).as_str());
+            packing.push(type_pack(&kind, &value));
+            unpacking.push(Unpack::Field(member, type_unpack(&kind)));
+            continue;
+        }
+        let literal_lens = lens
+            .iter()
+            .map(|len| match len {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(len),
+                    ..
+                }) => len.base10_parse::<usize>().ok(),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        let lens = lens
+            .iter()
+            .map(|len| format!("({})", len.into_token_stream()))
+            .collect::<Vec<_>>();
+        let len = lens.join(" * ");
+        // const lengths are only known to the compiler
+        match literal_lens {
+            Some(literal_lens) => schema.declare(
+                &kind,
+                &format!(
+                    "{}[{}]",
+                    schema_name,
+                    literal_lens.iter().product::<usize>()
+                ),
+            ),
+            None => {
+                schema.declare(&kind, &format!("{}[", schema_name));
+                schema.push_number(syn::parse_str::<TokenStream2>(&len)?);
+                schema.push_str("]");
             }
         }
-    } else {
-        panic!("Only structs with named fields are supported");
+        size_expr.push_str(format!("({} * {}) + ", type_size(&kind), len).as_str());
+        packing.push(array_type_pack(&kind, &value, &lens));
+        unpacking.push(Unpack::Field(member, array_type_unpack(&kind, lens.len())));
+    }
+    if let Some(unit) = unit.take() {
+        packing.push(unit.pack());
     }
     size_expr.push_str(" 0");
-    let size_expr = syn::parse_str::<syn::Expr>(size_expr.as_str()).unwrap();
-    let schema = syn::parse_str::<syn::LitStr>(format!("\"{}\"", schema).as_str()).unwrap();
+    let size_expr = syn::parse_str::<syn::Expr>(size_expr.as_str())?;
+
+    // generic params have to be structures themselves,
+    // each instantiation gets its own type string like `Pair_Meter` or `Buffer_4`
+    let mut generics = ast.generics.clone();
+    let mut type_str = Parts::default();
+    type_str.push_str(&type_attrs(ast)?.rename.unwrap_or_else(|| name.to_string()));
+    for param in generics.params.iter_mut() {
+        match param {
+            syn::GenericParam::Type(param) => {
+                param
+                    .bounds
+                    .push(syn::parse_quote!(frc_values::structure::FrcStructure));
+                type_str.push_str("_");
+                type_str.push_type(param.ident.to_token_stream());
+            }
+            syn::GenericParam::Const(param) => {
+                type_str.push_str("_");
+                type_str.push_number(param.ident.to_token_stream());
+            }
+            syn::GenericParam::Lifetime(_) => {}
+        }
+    }
+    let generic = !generics.params.is_empty();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    // the canonical form of `frc_values::structure::normalize_schema`
+    let (schema, schema_const) = schema.to_str("__FRC_SCHEMA", generic);
+    let (type_str, type_const) = type_str.to_str("__FRC_TYPE", generic);
+    let consts = if schema_const.is_empty() && type_const.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            impl #impl_generics #name #ty_generics #where_clause {
+                #schema_const
+                #type_const
+            }
+        }
+    };
+    // generic structures can only be registered once instantiated
+    let submit = if generic {
+        quote! {}
+    } else {
+        quote! {
+            frc_values::inventory::submit! { <#name as frc_values::structure::FrcStructure>::DESCRIPTION }
+            #(frc_values::inventory::submit! { <#schema_only as frc_values::structure::FrcStructure>::DESCRIPTION })*
+            // registers the field instantiations of generic structures as well
+            #(frc_values::inventory::submit! {
+                frc_values::structure::__derive::field_desc::<#nested>(<#name as frc_values::structure::FrcStructure>::DESCRIPTION)
+            })*
+        }
+    };
+
+    let validation = if validation.is_empty() {
+        quote! {}
+    } else {
+        let checks = validation
+            .iter()
+            .map(|s| syn::parse_str::<syn::Expr>(s))
+            .collect::<syn::Result<Vec<_>>>()?;
+        quote! {
+            fn is_valid(buffer: &[u8]) -> bool {
+                buffer.len() >= Self::SIZE #(&& #checks)*
+            }
+        }
+    };
+
     let packing = packing
         .iter()
-        .map(|s| syn::parse_str::<syn::Stmt>(s.as_str()).unwrap());
-    let unpacking = unpacking
-        .iter()
-        .map(|s| syn::parse_str::<syn::FieldValue>(s.as_str()).unwrap());
-    let expanded = quote! {
-        impl frc_values::structure::FrcStructure for #name {
+        .map(|s| syn::parse_str::<syn::Stmt>(s.as_str()))
+        .collect::<syn::Result<Vec<_>>>()?;
+    let mut unpack_stmts = Vec::new();
+    let mut unpack_fields = Vec::new();
+    for unpack in &unpacking {
+        match unpack {
+            Unpack::Stmt(stmt) => unpack_stmts.push(syn::parse_str::<syn::Stmt>(stmt)?),
+            Unpack::Field(member, expr) => {
+                let member = syn::parse_str::<syn::Member>(member)?;
+                let expr = syn::parse_str::<syn::Expr>(expr)?;
+                let local = match &member {
+                    syn::Member::Named(ident) => {
+                        quote::format_ident!("__frc_field_{}", ident.unraw())
+                    }
+                    syn::Member::Unnamed(index) => {
+                        quote::format_ident!("__frc_field_{}", index.index)
+                    }
+                };
+                unpack_stmts.push(syn::parse_quote! { let #local = #expr; });
+                unpack_fields.push(quote! { #member: #local });
+            }
+        }
+    }
+    Ok(quote! {
+        impl #impl_generics frc_values::structure::FrcStructure for #name #ty_generics #where_clause {
             const SIZE: usize = #size_expr;
             const SCHEMA: &'static str = #schema;
-            const TYPE: &'static str = stringify!(#name);
+            const TYPE: &'static str = #type_str;
             const DESCRIPTION: frc_values::structure::FrcStructDesc = frc_values::structure::FrcStructDesc {
                 schema: Self::SCHEMA,
                 type_str: Self::TYPE,
@@ -250,13 +885,180 @@ fn impl_frc_struct(ast: &syn::DeriveInput) -> TokenStream2 {
             }
 
             fn unpack(buffer: &mut impl frc_values::bytes::Buf) -> Self {
+                #(#unpack_stmts)*
                 Self {
-                    #(#unpacking),*
+                    #(#unpack_fields),*
+                }
+            }
+
+            #validation
+        }
+        #consts
+        #submit
+    })
+}
+
+/// Derive macro for implementing `FrcLoggable` on a struct,
+/// logging every field as its own topic
+///
+/// Fields are converted with `Into<FrcValue>` and rebuilt with `TryFrom<FrcValue>`,
+/// topics are named after the fields (`_0`, `_1`... for tuple structs)
+///
+/// Field attributes:
+/// - `#[frc_log(nested)]` logs a field that is `FrcLoggable` itself under `field/...`
+/// - `#[frc_log(structure)]` logs an `FrcStructure` field as a single struct topic
+/// - `#[frc_log(rename = "...")]` replaces the topic name
+/// - `#[frc_log(skip)]` doesn't log a field, it is rebuilt as its `Default`
+#[proc_macro_derive(FrcLoggable, attributes(frc_log))]
+pub fn frc_loggable(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+    impl_frc_loggable(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// The `#[frc_log(...)]` attributes of a field
+#[derive(Default)]
+struct LogAttrs {
+    rename: Option<String>,
+    skip: bool,
+    nested: bool,
+    structure: bool,
+}
+
+fn log_attrs(field: &syn::Field) -> syn::Result<LogAttrs> {
+    let mut attrs = LogAttrs::default();
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("frc_log"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                attrs.rename = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+            } else if meta.path.is_ident("skip") {
+                attrs.skip = true;
+            } else if meta.path.is_ident("nested") {
+                attrs.nested = true;
+            } else if meta.path.is_ident("structure") {
+                attrs.structure = true;
+            } else {
+                return Err(meta.error("unknown frc_log attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    if attrs.nested && attrs.structure {
+        return Err(syn::Error::new_spanned(
+            field,
+            "fields can't be both nested and a structure",
+        ));
+    }
+    Ok(attrs)
+}
+
+fn impl_frc_loggable(ast: &syn::DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &ast.ident;
+    let syn::Data::Struct(data) = &ast.data else {
+        return Err(syn::Error::new_spanned(
+            name,
+            "FrcLoggable can only be derived for structs",
+        ));
+    };
+    let mut logging = Vec::new();
+    let mut locals = Vec::new();
+    let mut rebuilding = Vec::new();
+    let mut members = Vec::new();
+    for (index, field) in data.fields.iter().enumerate() {
+        let (member, topic) = match &field.ident {
+            Some(ident) => (syn::Member::Named(ident.clone()), ident.unraw().to_string()),
+            None => (syn::Member::Unnamed(index.into()), format!("_{}", index)),
+        };
+        let attrs = log_attrs(field)?;
+        let topic = attrs.rename.unwrap_or(topic);
+        let ty = &field.ty;
+        let local = quote::format_ident!("__frc_field_{}", index);
+        let get = quote! {
+            {
+                let path = path(#topic);
+                match get(&path) {
+                    Some(value) => value,
+                    None => return Err(frc_values::FrcValueError::MissingField(path)),
                 }
             }
+        };
+        if attrs.skip {
+            rebuilding.push(quote! { core::default::Default::default() });
+        } else if attrs.nested {
+            logging.push(quote! {
+                frc_values::FrcLoggable::log_into(&self.#member, &path(#topic), values);
+            });
+            rebuilding.push(quote! {
+                <#ty as frc_values::FrcLoggable>::from_logged(&path(#topic), get)?
+            });
+        } else if attrs.structure {
+            logging.push(quote! {
+                values.push((
+                    path(#topic),
+                    frc_values::FrcValue::from_struct(core::clone::Clone::clone(&self.#member)),
+                ));
+            });
+            rebuilding.push(quote! { #get.try_into_struct::<#ty>()? });
+        } else {
+            logging.push(quote! {
+                values.push((
+                    path(#topic),
+                    frc_values::FrcValue::from(core::clone::Clone::clone(&self.#member)),
+                ));
+            });
+            rebuilding.push(quote! {
+                <#ty as core::convert::TryFrom<frc_values::FrcValue>>::try_from(#get)?
+            });
         }
-        frc_values::inventory::submit! { <#name as frc_values::structure::FrcStructure>::DESCRIPTION }
+        locals.push(local);
+        members.push(member);
+    }
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    // every field could be skipped
+    let (path, log_path) = if logging.is_empty() {
+        (
+            quote! { let _ = (prefix, get); },
+            quote! { let _ = (prefix, values); },
+        )
+    } else {
+        let path = quote! {
+            let path = |topic: &str| {
+                if prefix.is_empty() {
+                    topic.to_owned()
+                } else {
+                    format!("{}/{}", prefix, topic)
+                }
+            };
+        };
+        (path.clone(), path)
     };
 
-    expanded
+    Ok(quote! {
+        impl #impl_generics frc_values::FrcLoggable for #name #ty_generics #where_clause {
+            fn log_into(
+                &self,
+                prefix: &str,
+                values: &mut Vec<(String, frc_values::FrcValue)>,
+            ) {
+                #log_path
+                #(#logging)*
+            }
+
+            fn from_logged(
+                prefix: &str,
+                get: &dyn Fn(&str) -> Option<frc_values::FrcValue>,
+            ) -> Result<Self, frc_values::FrcValueError> {
+                #path
+                #(let #locals = #rebuilding;)*
+                Ok(Self {
+                    #(#members: #locals),*
+                })
+            }
+        }
+    })
 }
@@ -0,0 +1,8 @@
+use frc_values_macros::FrcStructure;
+
+#[derive(FrcStructure)]
+struct Robot {
+    speeds: [(f64, f64); 2],
+}
+
+fn main() {}
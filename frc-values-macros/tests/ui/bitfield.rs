@@ -0,0 +1,11 @@
+use frc_values_macros::FrcStructure;
+
+#[derive(FrcStructure)]
+struct Flags {
+    #[frc(bits = 9)]
+    small: u8,
+    #[frc(bits = 1)]
+    speed: f64,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use frc_values_macros::FrcStructure;
+
+#[derive(FrcStructure)]
+#[repr(u8)]
+enum Mode {
+    Disabled,
+    Auto(u8),
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use frc_values_macros::FrcStructure;
+
+#[derive(FrcStructure)]
+enum Mode {
+    Disabled,
+    Enabled,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use frc_values_macros::FrcLoggable;
+
+#[derive(FrcLoggable)]
+enum Mode {
+    Disabled,
+    Enabled,
+}
+
+fn main() {}
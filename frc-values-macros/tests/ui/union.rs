@@ -0,0 +1,9 @@
+use frc_values_macros::FrcStructure;
+
+#[derive(FrcStructure)]
+union Bits {
+    float: f32,
+    int: u32,
+}
+
+fn main() {}
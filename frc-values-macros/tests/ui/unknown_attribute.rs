@@ -0,0 +1,9 @@
+use frc_values_macros::FrcStructure;
+
+#[derive(FrcStructure)]
+struct Robot {
+    #[frc(flatten)]
+    speed: f64,
+}
+
+fn main() {}
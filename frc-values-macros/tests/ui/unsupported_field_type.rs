@@ -0,0 +1,9 @@
+use frc_values_macros::FrcStructure;
+
+#[derive(FrcStructure)]
+struct Robot {
+    speed: f64,
+    name: &'static str,
+}
+
+fn main() {}
@@ -0,0 +1,63 @@
+//! Prints rust structs deriving `FrcStructure` for struct schemas
+//!
+//! ```text
+//! frc-struct-codegen <Name> <schema> [<Name> <schema> ...]
+//! ```
+//!
+//! Names can have the `struct:` prefix of network tables type strings,
+//! a struct can be declared after the structs that contain it
+
+use frc_value::{
+    codegen::generate_structs,
+    structure::{FrcOwnedStructDesc, FrcStructDescDB},
+    FrcValueError,
+};
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.is_empty() || args.len() % 2 != 0 {
+        eprintln!("usage: frc-struct-codegen <Name> <schema> [<Name> <schema> ...]");
+        std::process::exit(2);
+    }
+    let mut pending = args
+        .chunks_exact(2)
+        .map(|pair| {
+            let name = pair[0].strip_prefix("struct:").unwrap_or(&pair[0]);
+            (name.to_owned(), pair[1].clone())
+        })
+        .collect::<Vec<_>>();
+
+    // a schema can only be sized once the structs it contains are registered
+    let mut descs = Vec::new();
+    while !pending.is_empty() {
+        let before = pending.len();
+        let mut last_error = None;
+        pending.retain(|(name, schema)| {
+            let registered = FrcOwnedStructDesc::from_schema(name.as_str(), schema.as_str())
+                .map_err(FrcValueError::from)
+                .and_then(|desc| FrcStructDescDB::register(desc.clone()).map(|_| desc));
+            match registered {
+                Ok(desc) => {
+                    descs.push(desc);
+                    false
+                }
+                Err(err) => {
+                    last_error = Some(format!("{}: {}", name, err));
+                    true
+                }
+            }
+        });
+        if pending.len() == before {
+            eprintln!("{}", last_error.unwrap_or_default());
+            std::process::exit(1);
+        }
+    }
+
+    match generate_structs(&descs) {
+        Ok(source) => print!("{}", source),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,493 @@
+use bytes::Bytes;
+
+#[cfg(feature = "protobuf")]
+use crate::proto::FrcProtoDesc;
+use crate::{
+    error::{CastErrorReason, FrcValueError},
+    structure::FrcStructDescHandle,
+    FrcType, FrcValue,
+};
+
+/// Variable length entries packed back to back,
+/// entry `i` is `data[offsets[i]..offsets[i + 1]]`
+#[derive(Debug, Clone, PartialEq)]
+struct Packed<T> {
+    data: Vec<T>,
+    offsets: Vec<usize>,
+}
+
+impl<T: Clone> Packed<T> {
+    fn with_capacity(capacity: usize) -> Self {
+        let mut offsets = Vec::with_capacity(capacity + 1);
+        offsets.push(0);
+        Self {
+            data: Vec::new(),
+            offsets,
+        }
+    }
+    fn push(&mut self, items: &[T]) {
+        self.data.extend_from_slice(items);
+        self.offsets.push(self.data.len());
+    }
+    fn get(&self, index: usize) -> Option<&[T]> {
+        let start = *self.offsets.get(index)?;
+        let end = *self.offsets.get(index + 1)?;
+        Some(&self.data[start..end])
+    }
+    fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+    fn clear(&mut self) {
+        self.data.clear();
+        self.offsets.truncate(1);
+    }
+}
+
+/// Same as [`Packed`] but for string data
+#[derive(Debug, Clone, PartialEq)]
+struct PackedStr {
+    data: String,
+    offsets: Vec<usize>,
+}
+
+impl PackedStr {
+    fn with_capacity(capacity: usize) -> Self {
+        let mut offsets = Vec::with_capacity(capacity + 1);
+        offsets.push(0);
+        Self {
+            data: String::new(),
+            offsets,
+        }
+    }
+    fn push(&mut self, item: &str) {
+        self.data.push_str(item);
+        self.offsets.push(self.data.len());
+    }
+    fn get(&self, index: usize) -> Option<&str> {
+        let start = *self.offsets.get(index)?;
+        let end = *self.offsets.get(index + 1)?;
+        Some(&self.data[start..end])
+    }
+    fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+    fn clear(&mut self) {
+        self.data.clear();
+        self.offsets.truncate(1);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Column {
+    Void(usize),
+    Boolean(Vec<bool>),
+    Int(Vec<i64>),
+    Double(Vec<f64>),
+    Float(Vec<f32>),
+    String(PackedStr),
+    BoolArray(Packed<bool>),
+    IntArray(Packed<i64>),
+    FloatArray(Packed<f32>),
+    DoubleArray(Packed<f64>),
+    /// every string of every array, plus the range of strings each array owns
+    StringArray(PackedStr, Vec<usize>),
+    Raw(Packed<u8>),
+    /// structs are fixed size so no offsets are needed,
+    /// the requested capacity (in values) is reserved once the size is known
+    /// and the number of values is counted as zero sized structs take up no data
+    Struct(Option<FrcStructDescHandle>, Vec<u8>, usize, usize),
+    StructArray(Option<FrcStructDescHandle>, Packed<u8>),
+    #[cfg(feature = "protobuf")]
+    Protobuf(Option<&'static FrcProtoDesc>, Packed<u8>),
+}
+
+/// A borrowed view of a string array stored in a [`FrcValueBuffer`]
+#[derive(Debug, Clone, Copy)]
+pub struct FrcStringArrayRef<'a> {
+    strings: &'a PackedStr,
+    start: usize,
+    end: usize,
+}
+
+impl<'a> FrcStringArrayRef<'a> {
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+    pub fn get(&self, index: usize) -> Option<&'a str> {
+        if index >= self.len() {
+            return None;
+        }
+        self.strings.get(self.start + index)
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &'a str> + 'a {
+        let strings = self.strings;
+        (self.start..self.end).filter_map(move |i| strings.get(i))
+    }
+    pub fn to_vec(&self) -> Vec<String> {
+        self.iter().map(|s| s.to_owned()).collect()
+    }
+}
+
+impl PartialEq for FrcStringArrayRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+/// A zero-copy view of a single value stored in a [`FrcValueBuffer`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrcValueRef<'a> {
+    Void,
+    Boolean(bool),
+    Int(i64),
+    Double(f64),
+    Float(f32),
+    String(&'a str),
+    BooleanArray(&'a [bool]),
+    IntArray(&'a [i64]),
+    FloatArray(&'a [f32]),
+    DoubleArray(&'a [f64]),
+    StringArray(FrcStringArrayRef<'a>),
+    Raw(&'a [u8]),
+    Struct(&'a FrcStructDescHandle, &'a [u8]),
+    StructArray(&'a FrcStructDescHandle, &'a [u8]),
+    #[cfg(feature = "protobuf")]
+    Protobuf(&'static FrcProtoDesc, &'a [u8]),
+}
+
+impl FrcValueRef<'_> {
+    pub fn get_type(&self) -> FrcType {
+        match self {
+            FrcValueRef::Void => FrcType::Void,
+            FrcValueRef::Boolean(_) => FrcType::Boolean,
+            FrcValueRef::Int(_) => FrcType::Int,
+            FrcValueRef::Double(_) => FrcType::Double,
+            FrcValueRef::Float(_) => FrcType::Float,
+            FrcValueRef::String(_) => FrcType::String,
+            FrcValueRef::BooleanArray(_) => FrcType::BoolArray,
+            FrcValueRef::IntArray(_) => FrcType::IntArray,
+            FrcValueRef::FloatArray(_) => FrcType::FloatArray,
+            FrcValueRef::DoubleArray(_) => FrcType::DoubleArray,
+            FrcValueRef::StringArray(_) => FrcType::StringArray,
+            FrcValueRef::Raw(_) => FrcType::Raw,
+            FrcValueRef::Struct(_, _) => FrcType::Struct,
+            FrcValueRef::StructArray(_, _) => FrcType::StructArray,
+            #[cfg(feature = "protobuf")]
+            FrcValueRef::Protobuf(_, _) => FrcType::Protobuf,
+        }
+    }
+    /// Copies the viewed data into an owned [`FrcValue`]
+    pub fn to_value(&self) -> FrcValue {
+        match *self {
+            FrcValueRef::Void => FrcValue::Void,
+            FrcValueRef::Boolean(v) => FrcValue::Boolean(v),
+            FrcValueRef::Int(v) => FrcValue::Int(v),
+            FrcValueRef::Double(v) => FrcValue::Double(v),
+            FrcValueRef::Float(v) => FrcValue::Float(v),
+            FrcValueRef::String(v) => FrcValue::String(v.to_owned()),
+            FrcValueRef::BooleanArray(v) => FrcValue::BooleanArray(v.to_vec()),
+            FrcValueRef::IntArray(v) => FrcValue::IntArray(v.to_vec()),
+            FrcValueRef::FloatArray(v) => FrcValue::FloatArray(v.to_vec()),
+            FrcValueRef::DoubleArray(v) => FrcValue::DoubleArray(v.to_vec()),
+            FrcValueRef::StringArray(v) => FrcValue::StringArray(v.to_vec()),
+            FrcValueRef::Raw(v) => FrcValue::Raw(Box::new(Bytes::copy_from_slice(v))),
+            FrcValueRef::Struct(desc, v) => {
+                FrcValue::Struct(desc.clone(), Box::new(Bytes::copy_from_slice(v)))
+            }
+            FrcValueRef::StructArray(desc, v) => {
+                FrcValue::StructArray(desc.clone(), Box::new(Bytes::copy_from_slice(v)))
+            }
+            #[cfg(feature = "protobuf")]
+            FrcValueRef::Protobuf(desc, v) => {
+                FrcValue::Protobuf(desc, Box::new(Bytes::copy_from_slice(v)))
+            }
+        }
+    }
+}
+
+impl From<FrcValueRef<'_>> for FrcValue {
+    fn from(value: FrcValueRef<'_>) -> Self {
+        value.to_value()
+    }
+}
+
+/// Stores many values of a single [`FrcType`] contiguously
+///
+/// Fixed size types are stored in one vec, strings, arrays and raw data
+/// are packed into one vec with an offset table,
+/// this avoids an allocation per value when loading large logs
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrcValueBuffer {
+    column: Column,
+}
+
+impl FrcValueBuffer {
+    pub fn new(r#type: FrcType) -> Self {
+        Self::with_capacity(r#type, 0)
+    }
+
+    /// `capacity` is the number of values, not the number of bytes
+    pub fn with_capacity(r#type: FrcType, capacity: usize) -> Self {
+        let column = match r#type {
+            FrcType::Void => Column::Void(0),
+            FrcType::Boolean => Column::Boolean(Vec::with_capacity(capacity)),
+            FrcType::Int => Column::Int(Vec::with_capacity(capacity)),
+            FrcType::Double => Column::Double(Vec::with_capacity(capacity)),
+            FrcType::Float => Column::Float(Vec::with_capacity(capacity)),
+            FrcType::String => Column::String(PackedStr::with_capacity(capacity)),
+            FrcType::BoolArray => Column::BoolArray(Packed::with_capacity(capacity)),
+            FrcType::IntArray => Column::IntArray(Packed::with_capacity(capacity)),
+            FrcType::FloatArray => Column::FloatArray(Packed::with_capacity(capacity)),
+            FrcType::DoubleArray => Column::DoubleArray(Packed::with_capacity(capacity)),
+            FrcType::StringArray => {
+                let mut offsets = Vec::with_capacity(capacity + 1);
+                offsets.push(0);
+                Column::StringArray(PackedStr::with_capacity(capacity), offsets)
+            }
+            FrcType::Raw => Column::Raw(Packed::with_capacity(capacity)),
+            FrcType::Struct => Column::Struct(None, Vec::new(), capacity, 0),
+            FrcType::StructArray => Column::StructArray(None, Packed::with_capacity(capacity)),
+            #[cfg(feature = "protobuf")]
+            FrcType::Protobuf => Column::Protobuf(None, Packed::with_capacity(capacity)),
+        };
+        Self { column }
+    }
+
+    /// Collects the values into a buffer of `r#type`, fails on the first value of a different type
+    pub fn try_from_values<'a>(
+        r#type: FrcType,
+        values: impl IntoIterator<Item = &'a FrcValue>,
+    ) -> Result<Self, FrcValueError> {
+        let values = values.into_iter();
+        let mut buffer = Self::with_capacity(r#type, values.size_hint().0);
+        for value in values {
+            buffer.push(value)?;
+        }
+        Ok(buffer)
+    }
+
+    pub fn get_type(&self) -> FrcType {
+        match self.column {
+            Column::Void(_) => FrcType::Void,
+            Column::Boolean(_) => FrcType::Boolean,
+            Column::Int(_) => FrcType::Int,
+            Column::Double(_) => FrcType::Double,
+            Column::Float(_) => FrcType::Float,
+            Column::String(_) => FrcType::String,
+            Column::BoolArray(_) => FrcType::BoolArray,
+            Column::IntArray(_) => FrcType::IntArray,
+            Column::FloatArray(_) => FrcType::FloatArray,
+            Column::DoubleArray(_) => FrcType::DoubleArray,
+            Column::StringArray(_, _) => FrcType::StringArray,
+            Column::Raw(_) => FrcType::Raw,
+            Column::Struct(_, _, _, _) => FrcType::Struct,
+            Column::StructArray(_, _) => FrcType::StructArray,
+            #[cfg(feature = "protobuf")]
+            Column::Protobuf(_, _) => FrcType::Protobuf,
+        }
+    }
+
+    /// The struct description shared by every value, `None` if not a struct (array) buffer or still empty
+    pub fn struct_desc(&self) -> Option<&FrcStructDescHandle> {
+        match &self.column {
+            Column::Struct(desc, _, _, _) => desc.as_ref(),
+            Column::StructArray(desc, _) => desc.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The message description shared by every value, `None` if not a protobuf buffer or still empty
+    #[cfg(feature = "protobuf")]
+    pub fn proto_desc(&self) -> Option<&'static FrcProtoDesc> {
+        match self.column {
+            Column::Protobuf(desc, _) => desc,
+            _ => None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.column {
+            Column::Void(count) => *count,
+            Column::Boolean(v) => v.len(),
+            Column::Int(v) => v.len(),
+            Column::Double(v) => v.len(),
+            Column::Float(v) => v.len(),
+            Column::String(v) => v.len(),
+            Column::BoolArray(v) => v.len(),
+            Column::IntArray(v) => v.len(),
+            Column::FloatArray(v) => v.len(),
+            Column::DoubleArray(v) => v.len(),
+            Column::StringArray(_, offsets) => offsets.len() - 1,
+            Column::Raw(v) => v.len(),
+            Column::Struct(_, _, _, len) => *len,
+            Column::StructArray(_, v) => v.len(),
+            #[cfg(feature = "protobuf")]
+            Column::Protobuf(_, v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends a copy of the value, the value must be the same type as the buffer
+    /// and structs must all share the same description
+    pub fn push(&mut self, value: &FrcValue) -> Result<(), FrcValueError> {
+        let buffer_type = self.get_type();
+        match (&mut self.column, value) {
+            (Column::Void(count), FrcValue::Void) => *count += 1,
+            (Column::Boolean(column), FrcValue::Boolean(v)) => column.push(*v),
+            (Column::Int(column), FrcValue::Int(v)) => column.push(*v),
+            (Column::Double(column), FrcValue::Double(v)) => column.push(*v),
+            (Column::Float(column), FrcValue::Float(v)) => column.push(*v),
+            (Column::String(column), FrcValue::String(v)) => column.push(v),
+            (Column::BoolArray(column), FrcValue::BooleanArray(v)) => column.push(v),
+            (Column::IntArray(column), FrcValue::IntArray(v)) => column.push(v),
+            (Column::FloatArray(column), FrcValue::FloatArray(v)) => column.push(v),
+            (Column::DoubleArray(column), FrcValue::DoubleArray(v)) => column.push(v),
+            (Column::StringArray(strings, offsets), FrcValue::StringArray(v)) => {
+                v.iter().for_each(|s| strings.push(s));
+                offsets.push(strings.len());
+            }
+            (Column::Raw(column), FrcValue::Raw(v)) => column.push(v),
+            (Column::Struct(column_desc, data, capacity, len), FrcValue::Struct(desc, v)) => {
+                let expected = column_desc.as_ref().unwrap_or(desc);
+                if expected.type_str() != desc.type_str() || v.len() != expected.size() {
+                    return Err(FrcValueError::InvalidCast(
+                        FrcType::Struct,
+                        expected.error_name(),
+                        CastErrorReason::Type,
+                    ));
+                }
+                if column_desc.is_none() {
+                    data.reserve(capacity.saturating_mul(desc.size()));
+                    *column_desc = Some(desc.clone());
+                }
+                data.extend_from_slice(v);
+                *len += 1;
+            }
+            (Column::StructArray(column_desc, column), FrcValue::StructArray(desc, v)) => {
+                let expected = column_desc.as_ref().unwrap_or(desc);
+                let whole_structs = match expected.size() {
+                    0 => v.is_empty(),
+                    size => v.len().is_multiple_of(size),
+                };
+                if expected.type_str() != desc.type_str() || !whole_structs {
+                    return Err(FrcValueError::InvalidCast(
+                        FrcType::StructArray,
+                        expected.error_name(),
+                        CastErrorReason::Type,
+                    ));
+                }
+                if column_desc.is_none() {
+                    *column_desc = Some(desc.clone());
+                }
+                column.push(v);
+            }
+            #[cfg(feature = "protobuf")]
+            (Column::Protobuf(column_desc, column), FrcValue::Protobuf(desc, v)) => {
+                let expected = column_desc.unwrap_or(desc);
+                if expected.type_str != desc.type_str {
+                    return Err(FrcValueError::InvalidCast(
+                        FrcType::Protobuf,
+                        &expected.type_str,
+                        CastErrorReason::Type,
+                    ));
+                }
+                *column_desc = Some(expected);
+                column.push(v);
+            }
+            _ => {
+                return Err(FrcValueError::InvalidCast(
+                    value.get_type(),
+                    buffer_type.name(),
+                    CastErrorReason::Type,
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, index: usize) -> Option<FrcValueRef<'_>> {
+        let value = match &self.column {
+            Column::Void(count) => {
+                if index >= *count {
+                    return None;
+                }
+                FrcValueRef::Void
+            }
+            Column::Boolean(v) => FrcValueRef::Boolean(*v.get(index)?),
+            Column::Int(v) => FrcValueRef::Int(*v.get(index)?),
+            Column::Double(v) => FrcValueRef::Double(*v.get(index)?),
+            Column::Float(v) => FrcValueRef::Float(*v.get(index)?),
+            Column::String(v) => FrcValueRef::String(v.get(index)?),
+            Column::BoolArray(v) => FrcValueRef::BooleanArray(v.get(index)?),
+            Column::IntArray(v) => FrcValueRef::IntArray(v.get(index)?),
+            Column::FloatArray(v) => FrcValueRef::FloatArray(v.get(index)?),
+            Column::DoubleArray(v) => FrcValueRef::DoubleArray(v.get(index)?),
+            Column::StringArray(strings, offsets) => FrcValueRef::StringArray(FrcStringArrayRef {
+                strings,
+                start: *offsets.get(index)?,
+                end: *offsets.get(index + 1)?,
+            }),
+            Column::Raw(v) => FrcValueRef::Raw(v.get(index)?),
+            Column::Struct(desc, data, _, len) => {
+                if index >= *len {
+                    return None;
+                }
+                let desc = desc.as_ref()?;
+                let start = index.checked_mul(desc.size())?;
+                let end = start.checked_add(desc.size())?;
+                FrcValueRef::Struct(desc, data.get(start..end)?)
+            }
+            Column::StructArray(desc, v) => FrcValueRef::StructArray(desc.as_ref()?, v.get(index)?),
+            #[cfg(feature = "protobuf")]
+            Column::Protobuf(desc, v) => FrcValueRef::Protobuf((*desc)?, v.get(index)?),
+        };
+        Some(value)
+    }
+
+    /// Copies the value at `index` into an owned [`FrcValue`]
+    pub fn get_value(&self, index: usize) -> Option<FrcValue> {
+        self.get(index).map(|v| v.to_value())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = FrcValueRef<'_>> + '_ {
+        (0..self.len()).filter_map(move |i| self.get(i))
+    }
+
+    pub fn to_values(&self) -> Vec<FrcValue> {
+        self.iter().map(|v| v.to_value()).collect()
+    }
+
+    /// Removes every value but keeps the allocations and the struct description
+    pub fn clear(&mut self) {
+        match &mut self.column {
+            Column::Void(count) => *count = 0,
+            Column::Boolean(v) => v.clear(),
+            Column::Int(v) => v.clear(),
+            Column::Double(v) => v.clear(),
+            Column::Float(v) => v.clear(),
+            Column::String(v) => v.clear(),
+            Column::BoolArray(v) => v.clear(),
+            Column::IntArray(v) => v.clear(),
+            Column::FloatArray(v) => v.clear(),
+            Column::DoubleArray(v) => v.clear(),
+            Column::StringArray(strings, offsets) => {
+                strings.clear();
+                offsets.truncate(1);
+            }
+            Column::Raw(v) => v.clear(),
+            Column::Struct(_, data, _, len) => {
+                data.clear();
+                *len = 0;
+            }
+            Column::StructArray(_, v) => v.clear(),
+            #[cfg(feature = "protobuf")]
+            Column::Protobuf(_, v) => v.clear(),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use crate::{
+    structure::{dependency_order, parse_declarations, Declaration, FrcOwnedStructDesc},
+    FrcValueError,
+};
+
+/// Keywords that can be used as raw identifiers
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "abstract", "become", "box", "do", "final", "macro", "override", "priv", "try",
+    "typeof", "unsized", "virtual", "yield",
+];
+
+/// A schema name as a rust identifier, keywords become raw identifiers
+fn rust_ident(name: &str) -> Result<String, FrcValueError> {
+    let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !matches!(name, "_" | "self" | "Self" | "super" | "crate");
+    if !valid {
+        return Err(FrcValueError::Codegen(format!(
+            "{} is not a valid rust identifier",
+            name
+        )));
+    }
+    if KEYWORDS.contains(&name) {
+        Ok(format!("r#{}", name))
+    } else {
+        Ok(name.to_owned())
+    }
+}
+
+fn rust_type(type_name: &str) -> Result<String, FrcValueError> {
+    let rust_type = match type_name {
+        "bool" => "bool",
+        "char" => "char",
+        "int8" => "i8",
+        "int16" => "i16",
+        "int32" => "i32",
+        "int64" => "i64",
+        "uint8" => "u8",
+        "uint16" => "u16",
+        "uint32" => "u32",
+        "uint64" => "u64",
+        "float" | "float32" => "f32",
+        "double" | "float64" => "f64",
+        name => return rust_ident(name),
+    };
+    Ok(rust_type.to_owned())
+}
+
+/// The `#[repr]` of an enum of the integer type and its range
+fn enum_repr(type_name: &str) -> Option<(&'static str, i128, i128)> {
+    let repr = match type_name {
+        "int8" => ("i8", i8::MIN as i128, i8::MAX as i128),
+        "int16" => ("i16", i16::MIN as i128, i16::MAX as i128),
+        "int32" => ("i32", i32::MIN as i128, i32::MAX as i128),
+        "int64" => ("i64", i64::MIN as i128, i64::MAX as i128),
+        "uint8" => ("u8", 0, u8::MAX as i128),
+        "uint16" => ("u16", 0, u16::MAX as i128),
+        "uint32" => ("u32", 0, u32::MAX as i128),
+        "uint64" => ("u64", 0, u64::MAX as i128),
+        _ => return None,
+    };
+    Some(repr)
+}
+
+/// `drive_mode` as `DriveMode`
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+        })
+        .collect()
+}
+
+/// A `#[repr]` enum deriving `FrcStructure` for an enum declaration,
+/// `None` if the variants can't be a rust enum (bit-fields, duplicate or out of range values)
+fn generate_enum(name: &str, declaration: &Declaration) -> Result<Option<String>, FrcValueError> {
+    let Some(variants) = &declaration.variants else {
+        return Ok(None);
+    };
+    let Some((repr, min, max)) = enum_repr(declaration.type_name) else {
+        return Ok(None);
+    };
+    let mut names = std::collections::HashSet::new();
+    let mut values = std::collections::HashSet::new();
+    let representable = declaration.width.is_none()
+        && !variants.is_empty()
+        && variants.iter().all(|(name, value)| {
+            (min..=max).contains(&(*value as i128)) && names.insert(name) && values.insert(value)
+        });
+    if !representable {
+        return Ok(None);
+    }
+    let mut body = String::new();
+    for (variant, value) in variants {
+        body.push_str(&format!("    {} = {},\n", rust_ident(variant)?, value));
+    }
+    // variants keep their schema names so the schema stays identical
+    let allow = if variants.iter().any(|(variant, _)| {
+        variant.starts_with(|c: char| !c.is_ascii_uppercase()) || variant.contains('_')
+    }) {
+        "#[allow(non_camel_case_types)]\n"
+    } else {
+        ""
+    };
+    Ok(Some(format!(
+        "/// `enum {{{}}} {}`\n#[derive(Debug, Clone, Copy, PartialEq, frc_values_macros::FrcStructure)]\n#[repr({})]\n{}pub enum {} {{\n{}}}\n",
+        variants
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join(", "),
+        declaration.type_name,
+        repr,
+        allow,
+        rust_ident(name)?,
+        body
+    )))
+}
+
+/// A struct deriving `FrcStructure` for the description,
+/// preceded by the enums of its enum fields
+fn generate_struct(desc: &FrcOwnedStructDesc) -> Result<String, FrcValueError> {
+    desc.validate()?;
+    let mut enums = String::new();
+    let mut fields = String::new();
+    for declaration in parse_declarations(&desc.schema)? {
+        let enum_name = format!("{}{}", desc.type_str, camel_case(declaration.name));
+        let field_type = match generate_enum(&enum_name, &declaration)? {
+            Some(generated) => {
+                enums.push_str(&generated);
+                enums.push('\n');
+                enum_name
+            }
+            None => {
+                if let Some(variants) = &declaration.variants {
+                    let variants = variants
+                        .iter()
+                        .map(|(name, value)| format!("{}={}", name, value))
+                        .collect::<Vec<_>>();
+                    fields.push_str(&format!("    /// enum {{{}}}\n", variants.join(", ")));
+                }
+                rust_type(declaration.type_name)?
+            }
+        };
+        if let Some((width, _)) = &declaration.width {
+            fields.push_str(&format!("    #[frc(bits = {})]\n", width));
+        }
+        let field_type = match declaration.count {
+            Some(count) => format!("[{}; {}]", field_type, count),
+            None => field_type,
+        };
+        fields.push_str(&format!(
+            "    pub {}: {},\n",
+            rust_ident(declaration.name)?,
+            field_type
+        ));
+    }
+    Ok(format!(
+        "{}/// `{}`\n#[derive(Debug, Clone, Copy, PartialEq, frc_values_macros::FrcStructure)]\npub struct {} {{\n{}}}\n",
+        enums,
+        desc.schema,
+        rust_ident(&desc.type_str)?,
+        fields
+    ))
+}
+
+/// Generates rust structs deriving `FrcStructure` for the descriptions
+/// and every struct they contain, nested structs are resolved through [`FrcStructDescDB`]
+///
+/// The generated structs pack to the same bytes as the schemas,
+/// enum fields get a `#[repr]` enum named after the struct and field (`PoseMode` for `mode` of `Pose`)
+/// unless the variants can't be a rust enum, then they are integers with the enum as doc comment,
+/// and structs are emitted before the structs that contain them
+///
+/// [`FrcStructDescDB`]: crate::structure::FrcStructDescDB
+pub fn generate_structs(descs: &[FrcOwnedStructDesc]) -> Result<String, FrcValueError> {
+    Ok(dependency_order(descs)?
+        .iter()
+        .map(|desc| generate_struct(desc))
+        .collect::<Result<Vec<_>, _>>()?
+        .join("\n"))
+}
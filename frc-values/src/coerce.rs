@@ -0,0 +1,153 @@
+use crate::{
+    error::{CastErrorReason, FrcValueError},
+    FrcType, FrcValue,
+};
+
+// 2^63, the first f64 above i64::MAX
+const I64_UPPER: f64 = 9_223_372_036_854_775_808.0;
+
+fn f64_to_i64(v: f64, lossy: bool) -> Result<i64, CastErrorReason> {
+    if v.is_nan() {
+        Err(CastErrorReason::PrecisionLoss)
+    } else if v >= I64_UPPER {
+        Err(CastErrorReason::Overflow)
+    } else if v < -I64_UPPER {
+        Err(CastErrorReason::Underflow)
+    } else if !lossy && v.fract() != 0.0 {
+        Err(CastErrorReason::PrecisionLoss)
+    } else {
+        Ok(v as i64)
+    }
+}
+
+fn i64_to_f64(v: i64, lossy: bool) -> Result<f64, CastErrorReason> {
+    let f = v as f64;
+    if !lossy && f as i128 != v as i128 {
+        Err(CastErrorReason::PrecisionLoss)
+    } else {
+        Ok(f)
+    }
+}
+
+fn i64_to_f32(v: i64, lossy: bool) -> Result<f32, CastErrorReason> {
+    let f = v as f32;
+    if !lossy && f as i128 != v as i128 {
+        Err(CastErrorReason::PrecisionLoss)
+    } else {
+        Ok(f)
+    }
+}
+
+fn f64_to_f32(v: f64, lossy: bool) -> Result<f32, CastErrorReason> {
+    if v.is_finite() && v > f32::MAX as f64 {
+        Err(CastErrorReason::Overflow)
+    } else if v.is_finite() && v < f32::MIN as f64 {
+        Err(CastErrorReason::Underflow)
+    } else if !lossy && !v.is_nan() && v as f32 as f64 != v {
+        Err(CastErrorReason::PrecisionLoss)
+    } else {
+        Ok(v as f32)
+    }
+}
+
+fn map_vec<A: Copy, B>(
+    values: &[A],
+    lossy: bool,
+    cast: fn(A, bool) -> Result<B, CastErrorReason>,
+) -> Result<Vec<B>, CastErrorReason> {
+    values.iter().map(|v| cast(*v, lossy)).collect()
+}
+
+impl FrcValue {
+    fn coerce(&self, r#type: FrcType, lossy: bool) -> Result<FrcValue, FrcValueError> {
+        if self.get_type() == r#type {
+            return Ok(self.clone());
+        }
+        let coerced = match (self, r#type) {
+            (FrcValue::Int(v), FrcType::Double) => i64_to_f64(*v, lossy).map(FrcValue::Double),
+            (FrcValue::Int(v), FrcType::Float) => i64_to_f32(*v, lossy).map(FrcValue::Float),
+            (FrcValue::Double(v), FrcType::Int) => f64_to_i64(*v, lossy).map(FrcValue::Int),
+            (FrcValue::Double(v), FrcType::Float) => f64_to_f32(*v, lossy).map(FrcValue::Float),
+            (FrcValue::Float(v), FrcType::Int) => f64_to_i64(*v as f64, lossy).map(FrcValue::Int),
+            (FrcValue::Float(v), FrcType::Double) => Ok(FrcValue::Double(*v as f64)),
+            (FrcValue::IntArray(v), FrcType::DoubleArray) => {
+                map_vec(v, lossy, i64_to_f64).map(FrcValue::DoubleArray)
+            }
+            (FrcValue::IntArray(v), FrcType::FloatArray) => {
+                map_vec(v, lossy, i64_to_f32).map(FrcValue::FloatArray)
+            }
+            (FrcValue::DoubleArray(v), FrcType::IntArray) => {
+                map_vec(v, lossy, f64_to_i64).map(FrcValue::IntArray)
+            }
+            (FrcValue::DoubleArray(v), FrcType::FloatArray) => {
+                map_vec(v, lossy, f64_to_f32).map(FrcValue::FloatArray)
+            }
+            (FrcValue::FloatArray(v), FrcType::IntArray) => {
+                map_vec(v, lossy, |v: f32, lossy| f64_to_i64(v as f64, lossy))
+                    .map(FrcValue::IntArray)
+            }
+            (FrcValue::FloatArray(v), FrcType::DoubleArray) => {
+                Ok(FrcValue::DoubleArray(v.iter().map(|v| *v as f64).collect()))
+            }
+            _ => Err(CastErrorReason::Type),
+        };
+        coerced.map_err(|reason| {
+            FrcValueError::InvalidCast(self.get_type(), r#type.name(), reason)
+        })
+    }
+
+    /// Converts between the numeric variants (and numeric array variants) without losing any information
+    ///
+    /// Fails with [`CastErrorReason::Overflow`] or [`CastErrorReason::Underflow`] if a value is out of range
+    /// and with [`CastErrorReason::PrecisionLoss`] if a value can't be represented exactly,
+    /// for example `Double(1.5)` to `Int` or `Int(2^53 + 1)` to `Double`
+    pub fn coerce_to(&self, r#type: FrcType) -> Result<FrcValue, FrcValueError> {
+        self.coerce(r#type, false)
+    }
+
+    /// Same as [`FrcValue::coerce_to`] but allows precision loss,
+    /// floating point values are truncated towards zero when converted to `Int`
+    pub fn coerce_to_lossy(&self, r#type: FrcType) -> Result<FrcValue, FrcValueError> {
+        self.coerce(r#type, true)
+    }
+
+    /// Reads an `Int`, `Float` or `Double` as an `i64` if it is an exact integer
+    pub fn as_i64_exact(&self) -> Result<i64, FrcValueError> {
+        self.read_i64(false)
+    }
+
+    /// Reads an `Int`, `Float` or `Double` as an `i64`, truncating any fractional part
+    pub fn as_i64_lossy(&self) -> Result<i64, FrcValueError> {
+        self.read_i64(true)
+    }
+
+    /// Reads an `Int`, `Float` or `Double` as an `f64` if it can be represented exactly
+    pub fn as_f64_exact(&self) -> Result<f64, FrcValueError> {
+        self.read_f64(false)
+    }
+
+    /// Reads an `Int`, `Float` or `Double` as an `f64`, large integers may be rounded
+    pub fn as_f64_lossy(&self) -> Result<f64, FrcValueError> {
+        self.read_f64(true)
+    }
+
+    fn read_i64(&self, lossy: bool) -> Result<i64, FrcValueError> {
+        match self {
+            FrcValue::Int(v) => Ok(*v),
+            FrcValue::Float(v) => f64_to_i64(*v as f64, lossy),
+            FrcValue::Double(v) => f64_to_i64(*v, lossy),
+            _ => Err(CastErrorReason::Type),
+        }
+        .map_err(|reason| FrcValueError::InvalidCast(self.get_type(), stringify!(i64), reason))
+    }
+
+    fn read_f64(&self, lossy: bool) -> Result<f64, FrcValueError> {
+        match self {
+            FrcValue::Int(v) => i64_to_f64(*v, lossy),
+            FrcValue::Float(v) => Ok(*v as f64),
+            FrcValue::Double(v) => Ok(*v),
+            _ => Err(CastErrorReason::Type),
+        }
+        .map_err(|reason| FrcValueError::InvalidCast(self.get_type(), stringify!(f64), reason))
+    }
+}
@@ -1,12 +1,44 @@
 use thiserror::Error;
 
-use crate::FrcType;
+use crate::{structure::SchemaCompatibility, FrcType};
 
 #[derive(Debug, Clone)]
 pub enum CastErrorReason {
     Type,
     Overflow,
-    Underflow
+    Underflow,
+    /// The value is in range but can't be represented exactly by the target type
+    PrecisionLoss,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaErrorReason {
+    /// Text that isn't part of the schema grammar
+    InvalidToken,
+    /// A declaration that isn't `[enum {..}] type name`, `type name[count]` or `type name:bits`
+    Syntax,
+    /// Something other than a type name or struct name in the type position
+    UnknownType,
+    DuplicateField,
+    /// A nested struct that isn't registered in the `FrcStructDescDB`
+    UnresolvedStruct,
+    /// A struct that contains itself
+    RecursiveStruct,
+    /// A bit-field on a non integer type or with a width that doesn't fit the type
+    InvalidBitfield,
+    /// An array or struct too large to address
+    TooLarge,
+    /// The fields don't add up to the size of the description
+    SizeMismatch { expected: usize, actual: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid schema at {position} ({token:?}): {reason:?}")]
+pub struct SchemaError {
+    /// Byte offset of the offending token in the schema
+    pub position: usize,
+    pub token: String,
+    pub reason: SchemaErrorReason,
 }
 
 
@@ -15,5 +47,24 @@ pub enum FrcValueError {
     #[error("Could not cast {0} variant to {1} type ({2:?})")]
     InvalidCast(FrcType, &'static str, CastErrorReason),
     #[error("Could not represent the casted data as an FrcValue")]
-    UnrepresentableCast
+    UnrepresentableCast,
+    #[error("Invalid type string: {0}")]
+    InvalidTypeString(String),
+    #[error("No description is registered for {0}")]
+    UnknownType(String),
+    #[error("No field named {0}")]
+    UnknownField(String),
+    #[error("Missing field {0}")]
+    MissingField(String),
+    #[error("Expected {expected} bytes but the buffer has {actual}")]
+    SizeMismatch { expected: usize, actual: usize },
+    #[error("{0}")]
+    InvalidSchema(#[from] SchemaError),
+    #[error("{0} is already registered with a different schema ({1:?})")]
+    SchemaConflict(String, SchemaCompatibility),
+    #[error("Could not generate code: {0}")]
+    Codegen(String),
+    #[cfg(feature = "protobuf")]
+    #[error("Protobuf error: {0}")]
+    Protobuf(String)
 }
\ No newline at end of file
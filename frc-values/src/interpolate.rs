@@ -0,0 +1,155 @@
+use crate::{
+    structure, FrcTimeline, FrcTimelineLookup, FrcTimestamp, FrcTimestampedValue, FrcValue,
+};
+
+/// How to find the value between two samples
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FrcInterpolation {
+    /// Zero-order hold, the last sample at or before the timestamp is used
+    #[default]
+    Hold,
+    /// Linear interpolation between the samples around the timestamp,
+    /// values that can't be interpolated fall back to [`FrcInterpolation::Hold`]
+    Linear,
+}
+
+fn lerp_f64(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+fn lerp_f32(a: f32, b: f32, t: f64) -> f32 {
+    lerp_f64(a as f64, b as f64, t) as f32
+}
+
+fn lerp_i64(a: i64, b: i64, t: f64) -> i64 {
+    (a as i128 + ((b as i128 - a as i128) as f64 * t).round() as i128) as i64
+}
+
+fn lerp_vec<T: Copy>(a: &[T], b: &[T], t: f64, lerp: impl Fn(T, T, f64) -> T) -> Option<Vec<T>> {
+    if a.len() != b.len() {
+        return None;
+    }
+    Some(a.iter().zip(b).map(|(a, b)| lerp(*a, *b, t)).collect())
+}
+
+impl FrcValue {
+    /// Linearly interpolates between `self` and `other`, `t` is clamped to `0.0..=1.0`
+    ///
+    /// Supported are `Int`, `Float`, `Double`, their arrays (if the lengths match)
+    /// and `Struct` or `StructArray` values of the same type (and length) whose schema only has numeric fields
+    /// that aren't enums or bit-fields,
+    /// any other combination returns `None`
+    pub fn lerp(&self, other: &FrcValue, t: f64) -> Option<FrcValue> {
+        let t = t.clamp(0.0, 1.0);
+        match (self, other) {
+            (FrcValue::Int(a), FrcValue::Int(b)) => Some(FrcValue::Int(lerp_i64(*a, *b, t))),
+            (FrcValue::Float(a), FrcValue::Float(b)) => Some(FrcValue::Float(lerp_f32(*a, *b, t))),
+            (FrcValue::Double(a), FrcValue::Double(b)) => {
+                Some(FrcValue::Double(lerp_f64(*a, *b, t)))
+            }
+            (FrcValue::IntArray(a), FrcValue::IntArray(b)) => {
+                lerp_vec(a, b, t, lerp_i64).map(FrcValue::IntArray)
+            }
+            (FrcValue::FloatArray(a), FrcValue::FloatArray(b)) => {
+                lerp_vec(a, b, t, lerp_f32).map(FrcValue::FloatArray)
+            }
+            (FrcValue::DoubleArray(a), FrcValue::DoubleArray(b)) => {
+                lerp_vec(a, b, t, lerp_f64).map(FrcValue::DoubleArray)
+            }
+            (FrcValue::Struct(desc_a, a), FrcValue::Struct(desc_b, b))
+                if desc_a.type_str() == desc_b.type_str() =>
+            {
+                structure::lerp_struct(desc_a, a, b, t)
+                    .map(|bytes| FrcValue::Struct(desc_a.clone(), Box::new(bytes)))
+            }
+            (FrcValue::StructArray(desc_a, a), FrcValue::StructArray(desc_b, b))
+                if desc_a.type_str() == desc_b.type_str()
+                    && a.len() == b.len()
+                    && desc_a.size() != 0
+                    && a.len().is_multiple_of(desc_a.size()) =>
+            {
+                let mut bytes = Vec::with_capacity(a.len());
+                for start in (0..a.len()).step_by(desc_a.size()) {
+                    let range = start..start + desc_a.size();
+                    let element = structure::lerp_struct(
+                        desc_a,
+                        &a.slice(range.clone()),
+                        &b.slice(range),
+                        t,
+                    )?;
+                    bytes.extend_from_slice(&element);
+                }
+                Some(FrcValue::StructArray(
+                    desc_a.clone(),
+                    Box::new(bytes.into()),
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl FrcTimestampedValue {
+    /// The value at `timestamp` between `self` and a later sample `next`
+    ///
+    /// Timestamps outside of the two samples are clamped to the closest sample
+    pub fn interpolate(
+        &self,
+        next: &FrcTimestampedValue,
+        timestamp: FrcTimestamp,
+        interpolation: FrcInterpolation,
+    ) -> FrcValue {
+        if interpolation == FrcInterpolation::Hold || next.timestamp <= self.timestamp {
+            return if timestamp >= next.timestamp {
+                next.value.clone()
+            } else {
+                self.value.clone()
+            };
+        }
+        let t = (timestamp.saturating_sub(self.timestamp)) as f64
+            / (next.timestamp - self.timestamp) as f64;
+        self.value.lerp(&next.value, t).unwrap_or_else(|| {
+            if t >= 1.0 {
+                next.value.clone()
+            } else {
+                self.value.clone()
+            }
+        })
+    }
+}
+
+impl FrcTimeline {
+    /// The value of the timeline at `timestamp`
+    ///
+    /// Returns `None` before the first sample, after the last sample the last value is held
+    pub fn sample(
+        &self,
+        timestamp: FrcTimestamp,
+        interpolation: FrcInterpolation,
+    ) -> Option<FrcValue> {
+        let before = self.index_by_timestamp(timestamp, FrcTimelineLookup::Before)?;
+        let prev = &self.as_slice()[before];
+        match self.as_slice().get(before + 1) {
+            Some(next) if prev.timestamp != timestamp => {
+                Some(prev.interpolate(next, timestamp, interpolation))
+            }
+            _ => Some(prev.value.clone()),
+        }
+    }
+
+    /// Samples the timeline at every timestamp, see [`FrcTimeline::sample`]
+    pub fn resample(
+        &self,
+        timestamps: impl IntoIterator<Item = FrcTimestamp>,
+        interpolation: FrcInterpolation,
+    ) -> FrcTimeline {
+        let values = timestamps
+            .into_iter()
+            .filter_map(|timestamp| {
+                self.sample(timestamp, interpolation)
+                    .map(|value| value.to_timestamped(timestamp))
+            })
+            .collect();
+        FrcTimeline::from_vec(values)
+    }
+}
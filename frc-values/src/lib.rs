@@ -4,23 +4,43 @@ use std::{
 };
 
 use bytes::Bytes;
-// use protobuf::descriptor::FileDescriptorProto;
 use serde::{Deserialize, Serialize};
 
+mod buffer;
+pub mod codegen;
+mod coerce;
 mod error;
+mod interpolate;
+#[cfg(feature = "protobuf")]
+pub mod proto;
 pub mod structure;
+mod table;
+mod tagged;
 #[cfg(test)]
 mod test;
+mod timeline;
 mod trait_impls;
 mod traits;
+mod type_string;
 
-pub use error::FrcValueError;
-use structure::FrcStructDesc;
-pub use traits::IntoFrcValue;
+pub use buffer::{FrcStringArrayRef, FrcValueBuffer, FrcValueRef};
+pub use error::{CastErrorReason, FrcValueError, SchemaError, SchemaErrorReason};
+pub use interpolate::FrcInterpolation;
+#[cfg(feature = "protobuf")]
+use proto::FrcProtoDesc;
+use structure::FrcStructDescHandle;
+pub use tagged::FrcValueSeed;
+pub use table::{
+    normalize_path, FrcSubTable, FrcTable, FrcTableDiff, FrcTableHistory, FrcTableInstant,
+};
+pub use timeline::{FrcTimeline, FrcTimelineLookup};
+pub use traits::{FrcLoggable, IntoFrcValue};
+pub use type_string::FrcTypeString;
 
 pub use bytes;
 pub use inventory;
-// pub use protobuf;
+#[cfg(feature = "protobuf")]
+pub use protobuf;
 
 /// Measured in microseconds <p>
 /// depending on source can be from unix epoch or some arbitrary start time
@@ -41,25 +61,35 @@ pub enum FrcType {
     StringArray,
     Raw,
     Struct,
-    // Protobuf,
+    StructArray,
+    #[cfg(feature = "protobuf")]
+    Protobuf,
 }
 impl Display for FrcType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+impl FrcType {
+    /// The name of the variant, same as the `Display` output
+    pub fn name(&self) -> &'static str {
         match self {
-            FrcType::Void => write!(f, "Void"),
-            FrcType::Boolean => write!(f, "Boolean"),
-            FrcType::Int => write!(f, "Int"),
-            FrcType::Double => write!(f, "Double"),
-            FrcType::Float => write!(f, "Float"),
-            FrcType::String => write!(f, "String"),
-            FrcType::BoolArray => write!(f, "BoolArray"),
-            FrcType::IntArray => write!(f, "IntArray"),
-            FrcType::FloatArray => write!(f, "FloatArray"),
-            FrcType::DoubleArray => write!(f, "DoubleArray"),
-            FrcType::StringArray => write!(f, "StringArray"),
-            FrcType::Raw => write!(f, "Raw"),
-            FrcType::Struct => write!(f, "Struct"),
-            // FrcType::Protobuf => write!(f, "Protobuf"),
+            FrcType::Void => "Void",
+            FrcType::Boolean => "Boolean",
+            FrcType::Int => "Int",
+            FrcType::Double => "Double",
+            FrcType::Float => "Float",
+            FrcType::String => "String",
+            FrcType::BoolArray => "BoolArray",
+            FrcType::IntArray => "IntArray",
+            FrcType::FloatArray => "FloatArray",
+            FrcType::DoubleArray => "DoubleArray",
+            FrcType::StringArray => "StringArray",
+            FrcType::Raw => "Raw",
+            FrcType::Struct => "Struct",
+            FrcType::StructArray => "StructArray",
+            #[cfg(feature = "protobuf")]
+            FrcType::Protobuf => "Protobuf",
         }
     }
 }
@@ -72,7 +102,25 @@ impl Serialize for FrcType {
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string().to_lowercase().replace("array", "[]"))
+        let s = match self {
+            FrcType::Void => "void",
+            FrcType::Boolean => "boolean",
+            FrcType::Int => "int",
+            FrcType::Double => "double",
+            FrcType::Float => "float",
+            FrcType::String => "string",
+            FrcType::BoolArray => "boolean[]",
+            FrcType::IntArray => "int[]",
+            FrcType::FloatArray => "float[]",
+            FrcType::DoubleArray => "double[]",
+            FrcType::StringArray => "string[]",
+            FrcType::Raw => "raw",
+            FrcType::Struct => "struct",
+            FrcType::StructArray => "struct[]",
+            #[cfg(feature = "protobuf")]
+            FrcType::Protobuf => "protobuf",
+        };
+        serializer.serialize_str(s)
     }
 }
 
@@ -83,23 +131,17 @@ impl<'a> Deserialize<'a> for FrcType {
     {
         let s = String::deserialize(deserializer)?;
         match s.as_str() {
-            "boolean" => Ok(FrcType::Boolean),
-            "int" => Ok(FrcType::Int),
-            "double" => Ok(FrcType::Double),
-            "float" => Ok(FrcType::Float),
-            "string" => Ok(FrcType::String),
-            "json" => Ok(FrcType::String),
+            "void" => Ok(FrcType::Void),
+            // older versions serialized BoolArray as "bool[]"
             "bool[]" => Ok(FrcType::BoolArray),
-            "int[]" => Ok(FrcType::IntArray),
-            "float[]" => Ok(FrcType::FloatArray),
-            "double[]" => Ok(FrcType::DoubleArray),
-            "string[]" => Ok(FrcType::StringArray),
-            "raw" => Ok(FrcType::Raw),
-            "rpc" => Ok(FrcType::Raw),
-            "msgpack" => Ok(FrcType::Raw),
-            // "protobuf" => Ok(FrcType::Protobuf),
+            #[cfg(feature = "protobuf")]
+            "protobuf" => Ok(FrcType::Protobuf),
             "struct" => Ok(FrcType::Struct),
-            _ => Err(serde::de::Error::custom(format!("Invalid FrcType: {}", s))),
+            "struct[]" => Ok(FrcType::StructArray),
+            _ => s
+                .parse::<FrcTypeString>()
+                .map(|type_string| type_string.frc_type())
+                .map_err(|_| serde::de::Error::custom(format!("Invalid FrcType: {}", s))),
         }
     }
 }
@@ -121,8 +163,9 @@ impl<'a> Deserialize<'a> for FrcType {
 /// - StringArray
 /// - Raw(Bytes)
 /// - Struct
+/// - StructArray
 ///
-/// Struct and Protobuf are special types that carry metadata to allow them to be decoded into their inner types
+/// Struct, StructArray and Protobuf are special types that carry metadata to allow them to be decoded into their inner types
 ///
 /// Bytes are Boxed to keep the size of the enum small
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -141,9 +184,13 @@ pub enum FrcValue {
     StringArray(Vec<String>),
     Raw(Box<Bytes>),
     #[serde(skip_deserializing)]
-    Struct(#[serde(skip)] &'static FrcStructDesc, Box<Bytes>),
-    // #[serde(skip_deserializing)]
-    // Protobuf(#[serde(skip)] &'static FileDescriptorProto, Box<Bytes>),
+    Struct(#[serde(skip)] FrcStructDescHandle, Box<Bytes>),
+    /// Many instances of the same struct packed back to back
+    #[serde(skip_deserializing)]
+    StructArray(#[serde(skip)] FrcStructDescHandle, Box<Bytes>),
+    #[cfg(feature = "protobuf")]
+    #[serde(skip_deserializing)]
+    Protobuf(#[serde(skip)] &'static FrcProtoDesc, Box<Bytes>),
 }
 impl Display for FrcValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -160,13 +207,12 @@ impl Display for FrcValue {
             FrcValue::DoubleArray(v) => write!(f, "{:?}", v),
             FrcValue::StringArray(v) => write!(f, "{:?}", v),
             FrcValue::Raw(v) => write!(f, "{:?}", v),
-            FrcValue::Struct(desc, data) => write!(f, "Struct({}):{:?}", desc.type_str, data),
-            // FrcValue::Protobuf(proto, data) => write!(
-            //     f,
-            //     "Protobuf({}):{:?}",
-            //     proto.name.clone().unwrap_or("unknown".into()),
-            //     data
-            // ),
+            FrcValue::Struct(desc, data) => write!(f, "Struct({}):{:?}", desc.type_str(), data),
+            FrcValue::StructArray(desc, data) => {
+                write!(f, "StructArray({}[]):{:?}", desc.type_str(), data)
+            }
+            #[cfg(feature = "protobuf")]
+            FrcValue::Protobuf(desc, data) => write!(f, "Protobuf({}):{:?}", desc.type_str, data),
         }
     }
 }
@@ -185,14 +231,15 @@ impl Hash for FrcValue {
             FrcValue::DoubleArray(v) => v.iter().for_each(|v| v.to_bits().hash(state)),
             FrcValue::StringArray(v) => v.hash(state),
             FrcValue::Raw(v) => v.hash(state),
-            FrcValue::Struct(desc, data) => {
-                desc.schema.hash(state);
+            FrcValue::Struct(desc, data) | FrcValue::StructArray(desc, data) => {
+                desc.fingerprint().hash(state);
+                data.hash(state);
+            }
+            #[cfg(feature = "protobuf")]
+            FrcValue::Protobuf(desc, data) => {
                 desc.type_str.hash(state);
                 data.hash(state);
-            } // FrcValue::Protobuf(proto, data) => {
-              //     proto.name.hash(state);
-              //     data.hash(state);
-              // }
+            }
         }
     }
 }
@@ -213,7 +260,9 @@ impl FrcValue {
             FrcValue::StringArray(_) => FrcType::StringArray,
             FrcValue::Raw(_) => FrcType::Raw,
             FrcValue::Struct(_, _) => FrcType::Struct,
-            // FrcValue::Protobuf(_, _) => FrcType::Protobuf,
+            FrcValue::StructArray(_, _) => FrcType::StructArray,
+            #[cfg(feature = "protobuf")]
+            FrcValue::Protobuf(_, _) => FrcType::Protobuf,
         }
     }
     ///Creates an empty Binary
@@ -232,19 +281,95 @@ impl FrcValue {
             FrcValue::StringArray(v) => v.is_empty(),
             FrcValue::Raw(v) => v.is_empty(),
             FrcValue::Struct(_, v) => v.is_empty(),
-            // FrcValue::Protobuf(_, v) => v.is_empty(),
+            FrcValue::StructArray(_, v) => v.is_empty(),
+            #[cfg(feature = "protobuf")]
+            FrcValue::Protobuf(_, v) => v.is_empty(),
             _ => false,
         }
     }
     ///Binary is false
     pub fn is_array(&self) -> bool {
+        matches!(
+            self,
+            FrcValue::BooleanArray(_)
+                | FrcValue::IntArray(_)
+                | FrcValue::DoubleArray(_)
+                | FrcValue::FloatArray(_)
+                | FrcValue::StringArray(_)
+                | FrcValue::StructArray(_, _)
+        )
+    }
+    pub fn as_bool(&self) -> Option<bool> {
         match self {
-            FrcValue::BooleanArray(_) => true,
-            FrcValue::IntArray(_) => true,
-            FrcValue::DoubleArray(_) => true,
-            FrcValue::FloatArray(_) => true,
-            FrcValue::StringArray(_) => true,
-            _ => false,
+            FrcValue::Boolean(v) => Some(*v),
+            _ => None,
+        }
+    }
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            FrcValue::Int(v) => Some(*v),
+            _ => None,
+        }
+    }
+    ///Floats are widened to f64
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            FrcValue::Double(v) => Some(*v),
+            FrcValue::Float(v) => Some(*v as f64),
+            _ => None,
+        }
+    }
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            FrcValue::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            FrcValue::String(v) => Some(v),
+            _ => None,
+        }
+    }
+    pub fn as_bool_slice(&self) -> Option<&[bool]> {
+        match self {
+            FrcValue::BooleanArray(v) => Some(v),
+            _ => None,
+        }
+    }
+    pub fn as_i64_slice(&self) -> Option<&[i64]> {
+        match self {
+            FrcValue::IntArray(v) => Some(v),
+            _ => None,
+        }
+    }
+    pub fn as_f32_slice(&self) -> Option<&[f32]> {
+        match self {
+            FrcValue::FloatArray(v) => Some(v),
+            _ => None,
+        }
+    }
+    pub fn as_f64_slice(&self) -> Option<&[f64]> {
+        match self {
+            FrcValue::DoubleArray(v) => Some(v),
+            _ => None,
+        }
+    }
+    pub fn as_string_slice(&self) -> Option<&[String]> {
+        match self {
+            FrcValue::StringArray(v) => Some(v),
+            _ => None,
+        }
+    }
+    ///The packed bytes of a Raw, Struct, StructArray or Protobuf value
+    pub fn as_bytes(&self) -> Option<&Bytes> {
+        match self {
+            FrcValue::Raw(v) => Some(v),
+            FrcValue::Struct(_, v) => Some(v),
+            FrcValue::StructArray(_, v) => Some(v),
+            #[cfg(feature = "protobuf")]
+            FrcValue::Protobuf(_, v) => Some(v),
+            _ => None,
         }
     }
     /// Consumes itself to a timestamped value with the given timestamp
@@ -266,6 +391,8 @@ impl FrcValue {
     /// Types that will return none:
     ///     - Void
     ///     - Struct
+    ///     - StructArray
+    ///     - Protobuf
     pub fn default_value(r#type: FrcType) -> Option<Self> {
         match r#type {
             FrcType::Void => None,
@@ -280,14 +407,18 @@ impl FrcValue {
             FrcType::DoubleArray => Some(FrcValue::DoubleArray(Vec::new())),
             FrcType::StringArray => Some(FrcValue::StringArray(Vec::new())),
             FrcType::Raw => Some(FrcValue::Raw(Box::new(Bytes::new()))),
-            FrcType::Struct => None, // FrcType::Protobuf => None,
+            FrcType::Struct => None,
+            FrcType::StructArray => None,
+            #[cfg(feature = "protobuf")]
+            FrcType::Protobuf => None,
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Hash)]
+/// A value together with its type, unlike a bare [`FrcValue`] this round-trips through serde
+/// for every variant, see the `Serialize` impl for the format
+#[derive(Debug, Clone, PartialEq, Hash)]
 pub struct FrcTaggedValue {
-    #[serde(rename = "type")]
     pub r#type: FrcType,
     pub value: FrcValue,
 }
@@ -343,141 +474,3 @@ impl FrcTimestampedValue {
         self.value = other.value;
     }
 }
-
-// #[derive(Debug, Clone, Serialize, Deserialize)]
-// pub struct FrcTimeline(Vec<FrcTimestampedValue>);
-
-// impl IntoIterator for FrcTimeline {
-//     type Item = FrcTimestampedValue;
-//     type IntoIter = std::vec::IntoIter<Self::Item>;
-//     fn into_iter(self) -> Self::IntoIter {
-//         self.0.into_iter()
-//     }
-// }
-
-// impl FrcTimeline {
-//     pub fn new() -> Self {
-//         Self(Vec::new())
-//     }
-//     pub fn from_vec_sorted(vec: Vec<FrcTimestampedValue>) -> Self {
-//         Self(vec)
-//     }
-//     pub fn from_vec(mut vec: Vec<FrcTimestampedValue>) -> Self {
-//         vec.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-//         Self(vec)
-//     }
-//     pub fn to_vec(self) -> Vec<FrcTimestampedValue> {
-//         self.0
-//     }
-//     pub fn is_all_same_type(&self) -> bool {
-//         if self.0.is_empty() {
-//             return true;
-//         }
-//         let first_type = self.0[0].get_type();
-//         self.0.iter().all(|v| v.get_type() == first_type)
-//     }
-//     pub fn is_all_same_type_as(&self, other: &FrcType) -> bool {
-//         if self.0.is_empty() {
-//             return true;
-//         }
-//         self.0.iter().all(|v| v.get_type() == *other)
-//     }
-//     pub fn is_empty(&self) -> bool {
-//         self.0.is_empty()
-//     }
-//     pub fn len(&self) -> usize {
-//         self.0.len()
-//     }
-//     /// if closest above will get the value with the closest timestamp that is after the given timestamp
-//     /// if closest above is false, will get the value with the closest timestamp that is before the given timestamp
-//     pub fn get_by_timestamp(
-//         &self,
-//         timestamp: u64,
-//         closest_after: bool,
-//     ) -> Option<&FrcTimestampedValue> {
-//         if closest_after {
-//             if timestamp < self.0[0].timestamp {
-//                 return None;
-//             }
-//         } else {
-//             if timestamp > self.0[self.0.len() - 1].timestamp {
-//                 return None;
-//             }
-//         }
-//         //use a bisect algorithm to find the closest value
-//         let mut low = 0;
-//         let mut high = self.0.len() - 1;
-//         while low <= high {
-//             let mid = (low + high) / 2;
-//             if self.0[mid].timestamp < timestamp {
-//                 low = mid + 1;
-//             } else if self.0[mid].timestamp > timestamp {
-//                 high = mid - 1;
-//             } else {
-//                 return Some(&self.0[mid]);
-//             }
-//         }
-//         if low == self.0.len() {
-//             return None;
-//         }
-//         if closest_after {
-//             Some(&self.0[low])
-//         } else {
-//             Some(&self.0[low - 1])
-//         }
-//     }
-// }
-
-// #[derive(Debug, Clone, Serialize, Deserialize)]
-// pub struct FrcTableInstant {
-//     #[serde(flatten)]
-//     pub values: HashMap<String, FrcTimestampedValue>, //just now
-// }
-// impl Display for FrcTableInstant {
-//     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//         write!(f, "{{")?;
-//         for (i, (k, v)) in self.values.iter().enumerate() {
-//             if i != 0 {
-//                 write!(f, ", ")?;
-//             }
-//             write!(f, "{}: {}", k, v)?;
-//         }
-//         write!(f, "}}")
-//     }
-// }
-// impl FrcTableInstant {
-//     pub fn new_slim() -> Self {
-//         Self {
-//             values: HashMap::new(),
-//         }
-//     }
-//     pub fn new() -> Self {
-//         Self {
-//             values: HashMap::new(),
-//         }
-//     }
-//     pub fn from_tuples(mut tuples: Vec<(impl ToString, FrcTimestampedValue)>) -> Self {
-//         let mut values = HashMap::new();
-//         tuples.reverse();
-//         for (k, v) in tuples {
-//             values.insert(k.to_string(), v);
-//         }
-//         Self { values }
-//     }
-//     pub fn set_field(&mut self, name: impl ToString, value: FrcTimestampedValue) {
-//         self.values.insert(name.to_string(), value);
-//     }
-// }
-
-// #[derive(Debug, Clone, Serialize, Deserialize, Default)]
-// pub struct FrcTableHistory {
-//     #[serde(flatten)]
-//     pub values: HashMap<String, FrcTimeline>, //all values that have occured
-// }
-
-// #[derive(Debug, Clone, Serialize, Deserialize)]
-// #[serde(tag = "type", content = "table", rename_all = "lowercase")]
-// pub enum FrcTable {
-//     Instant(FrcTableInstant),
-//     History(FrcTableHistory),
-// }
@@ -0,0 +1,240 @@
+use std::sync::RwLock;
+
+use bytes::Bytes;
+use protobuf::{
+    descriptor::FileDescriptorProto,
+    reflect::{
+        FileDescriptor, MessageDescriptor, ReflectRepeatedRef, ReflectValueRef, RuntimeFieldType,
+        RuntimeType,
+    },
+    Message, MessageDyn, MessageFull,
+};
+
+use crate::{error::CastErrorReason, FrcValue, FrcValueError};
+
+/// A protobuf message type that can be carried by [`FrcValue::Protobuf`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FrcProtoDesc {
+    /// The full name of the message, e.g. `wpi.proto.ProtobufPose2d`
+    pub type_str: String,
+    pub descriptor: MessageDescriptor,
+}
+
+impl FrcProtoDesc {
+    /// Decodes a serialized message into its fields,
+    /// nested messages are flattened with `.` separated names (`translation.x`)
+    /// and repeated messages or bytes are indexed (`modules[0].angle`)
+    ///
+    /// Unset message fields and map fields are not decoded
+    pub fn decode(&self, data: &[u8]) -> Result<Vec<(String, FrcValue)>, FrcValueError> {
+        let message = self
+            .descriptor
+            .parse_from_bytes(data)
+            .map_err(|err| FrcValueError::Protobuf(err.to_string()))?;
+        let mut fields = Vec::new();
+        decode_message(&*message, "", &mut fields);
+        Ok(fields)
+    }
+}
+
+fn decode_message(message: &dyn MessageDyn, prefix: &str, out: &mut Vec<(String, FrcValue)>) {
+    for field in message.descriptor_dyn().fields() {
+        let name = format!("{}{}", prefix, field.name());
+        match field.runtime_field_type() {
+            // the default of a message can contain the message again, so only set ones are decoded
+            RuntimeFieldType::Singular(RuntimeType::Message(_)) => {
+                if let Some(value) = field.get_singular(message) {
+                    decode_value(value, name, out)
+                }
+            }
+            RuntimeFieldType::Singular(_) => {
+                decode_value(field.get_singular_field_or_default(message), name, out)
+            }
+            RuntimeFieldType::Repeated(element_type) => {
+                decode_repeated(element_type, field.get_repeated(message), name, out)
+            }
+            RuntimeFieldType::Map(_, _) => {}
+        }
+    }
+}
+
+fn value_as_int(value: &ReflectValueRef) -> Option<i64> {
+    match value {
+        ReflectValueRef::I32(v) => Some(*v as i64),
+        ReflectValueRef::I64(v) => Some(*v),
+        ReflectValueRef::U32(v) => Some(*v as i64),
+        ReflectValueRef::U64(v) => Some(*v as i64),
+        ReflectValueRef::Enum(_, v) => Some(*v as i64),
+        _ => None,
+    }
+}
+
+fn decode_value(value: ReflectValueRef, name: String, out: &mut Vec<(String, FrcValue)>) {
+    let value = match value {
+        ReflectValueRef::F32(v) => FrcValue::Float(v),
+        ReflectValueRef::F64(v) => FrcValue::Double(v),
+        ReflectValueRef::Bool(v) => FrcValue::Boolean(v),
+        ReflectValueRef::String(v) => FrcValue::String(v.to_owned()),
+        ReflectValueRef::Bytes(v) => FrcValue::Raw(Box::new(Bytes::copy_from_slice(v))),
+        ReflectValueRef::Message(message) => {
+            decode_message(&*message, &format!("{}.", name), out);
+            return;
+        }
+        int => FrcValue::Int(value_as_int(&int).unwrap_or_default()),
+    };
+    out.push((name, value));
+}
+
+fn decode_repeated(
+    element_type: RuntimeType,
+    values: ReflectRepeatedRef,
+    name: String,
+    out: &mut Vec<(String, FrcValue)>,
+) {
+    let value = match element_type {
+        RuntimeType::F32 => {
+            FrcValue::FloatArray(values.into_iter().filter_map(|v| v.to_f32()).collect())
+        }
+        RuntimeType::F64 => {
+            FrcValue::DoubleArray(values.into_iter().filter_map(|v| v.to_f64()).collect())
+        }
+        RuntimeType::Bool => {
+            FrcValue::BooleanArray(values.into_iter().filter_map(|v| v.to_bool()).collect())
+        }
+        RuntimeType::String => FrcValue::StringArray(
+            values
+                .into_iter()
+                .filter_map(|v| v.to_str().map(|s| s.to_owned()))
+                .collect(),
+        ),
+        RuntimeType::VecU8 | RuntimeType::Message(_) => {
+            for (i, value) in values.into_iter().enumerate() {
+                decode_value(value, format!("{}[{}]", name, i), out);
+            }
+            return;
+        }
+        _ => FrcValue::IntArray(
+            values
+                .into_iter()
+                .filter_map(|v| value_as_int(&v))
+                .collect(),
+        ),
+    };
+    out.push((name, value));
+}
+
+static FILES: RwLock<Vec<FileDescriptor>> = RwLock::new(Vec::new());
+static MESSAGES: RwLock<Vec<&'static FrcProtoDesc>> = RwLock::new(Vec::new());
+
+pub struct FrcProtoDescDB;
+
+impl FrcProtoDescDB {
+    /// Registers every message of a file, the files dependencies have to be registered first
+    ///
+    /// Call very sparringly as this function leaks memory
+    pub fn add_file(proto: FileDescriptorProto) -> Result<(), FrcValueError> {
+        let mut files = FILES.write().expect("FrcProtoDescDB files poisoned");
+        if files.iter().any(|file| file.name() == proto.name()) {
+            return Ok(());
+        }
+        let mut dependencies = Vec::with_capacity(proto.dependency.len());
+        for dependency in &proto.dependency {
+            match files.iter().find(|file| file.name() == dependency) {
+                Some(file) => dependencies.push(file.clone()),
+                None => {
+                    return Err(FrcValueError::Protobuf(format!(
+                        "{} depends on unregistered file {}",
+                        proto.name(),
+                        dependency
+                    )))
+                }
+            }
+        }
+        let file = FileDescriptor::new_dynamic(proto, &dependencies)
+            .map_err(|err| FrcValueError::Protobuf(err.to_string()))?;
+        for message in file.messages() {
+            Self::add_message(message);
+        }
+        files.push(file);
+        Ok(())
+    }
+
+    /// Registers a serialized `FileDescriptorProto`,
+    /// the format published on `/.schema/proto:` topics
+    pub fn add_file_bytes(bytes: &[u8]) -> Result<(), FrcValueError> {
+        let proto = FileDescriptorProto::parse_from_bytes(bytes)
+            .map_err(|err| FrcValueError::Protobuf(err.to_string()))?;
+        Self::add_file(proto)
+    }
+
+    /// Registers a message and its nested messages, returns the existing entry if already registered
+    ///
+    /// Call very sparringly as this function leaks memory
+    pub fn add_message(descriptor: MessageDescriptor) -> &'static FrcProtoDesc {
+        for nested in descriptor.nested_messages() {
+            Self::add_message(nested);
+        }
+        let mut messages = MESSAGES.write().expect("FrcProtoDescDB messages poisoned");
+        if let Some(desc) = messages
+            .iter()
+            .find(|desc| desc.type_str == descriptor.full_name())
+        {
+            return desc;
+        }
+        let desc = Box::leak(Box::new(FrcProtoDesc {
+            type_str: descriptor.full_name().to_owned(),
+            descriptor,
+        }));
+        messages.push(desc);
+        desc
+    }
+
+    pub fn contains(type_str: &str) -> bool {
+        Self::get(type_str).is_some()
+    }
+
+    pub fn get(type_str: &str) -> Option<&'static FrcProtoDesc> {
+        MESSAGES
+            .read()
+            .expect("FrcProtoDescDB messages poisoned")
+            .iter()
+            .find(|desc| desc.type_str == type_str)
+            .copied()
+    }
+}
+
+impl FrcValue {
+    pub fn from_message<M: MessageFull>(message: &M) -> Result<Self, FrcValueError> {
+        let desc = FrcProtoDescDB::add_message(M::descriptor());
+        let bytes = message
+            .write_to_bytes()
+            .map_err(|err| FrcValueError::Protobuf(err.to_string()))?;
+        Ok(Self::Protobuf(desc, Box::new(Bytes::from(bytes))))
+    }
+
+    pub fn try_into_message<M: MessageFull>(self) -> Result<M, FrcValueError> {
+        match self {
+            Self::Protobuf(desc, buffer) if desc.descriptor == M::descriptor() => {
+                M::parse_from_tokio_bytes(&buffer)
+                    .map_err(|err| FrcValueError::Protobuf(err.to_string()))
+            }
+            _ => Err(FrcValueError::InvalidCast(
+                self.get_type(),
+                std::any::type_name::<M>(),
+                CastErrorReason::Type,
+            )),
+        }
+    }
+
+    /// Decodes a protobuf value into its fields, see [`FrcProtoDesc::decode`]
+    pub fn decode_protobuf(&self) -> Result<Vec<(String, FrcValue)>, FrcValueError> {
+        match self {
+            Self::Protobuf(desc, buffer) => desc.decode(buffer),
+            _ => Err(FrcValueError::InvalidCast(
+                self.get_type(),
+                "Protobuf",
+                CastErrorReason::Type,
+            )),
+        }
+    }
+}
@@ -1,9 +1,17 @@
-use std::collections::HashMap;
+use std::{
+    borrow::{Borrow, Cow},
+    collections::HashMap,
+    hash::Hash,
+    sync::{Arc, OnceLock, RwLock},
+};
 
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use logos::Logos;
 
-use crate::{error::CastErrorReason, FrcValue, FrcValueError};
+use crate::{
+    error::{CastErrorReason, SchemaError, SchemaErrorReason},
+    FrcType, FrcTypeString, FrcValue, FrcValueError,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct FrcStructDesc {
@@ -12,46 +20,571 @@ pub struct FrcStructDesc {
     pub size: usize,
 }
 
+impl FrcStructDesc {
+    /// The network tables type string of a single instance, `struct:Name`
+    pub fn type_string(&self) -> FrcTypeString {
+        FrcTypeString::Struct(self.type_str.to_owned())
+    }
+
+    /// The network tables type string of an array, `struct:Name[]`
+    pub fn array_type_string(&self) -> FrcTypeString {
+        FrcTypeString::StructArray(self.type_str.to_owned())
+    }
+
+    /// Checks the schema parses, every nested struct is registered
+    /// and the fields add up to `size`
+    pub fn validate(&self) -> Result<(), SchemaError> {
+        validate_schema(self.type_str, self.schema, self.size)
+    }
+
+    /// See [`schema_fingerprint`], cached for registered descriptions
+    pub fn fingerprint(&self) -> u64 {
+        let registered = static_descs()
+            .read()
+            .expect("FrcStructDescDB static descriptions poisoned")
+            .by_name
+            .get(self.type_str)
+            .filter(|(desc, _)| *desc == self)
+            .map(|(_, fingerprint)| *fingerprint);
+        registered.unwrap_or_else(|| schema_fingerprint(self.type_str, self.schema))
+    }
+
+    /// The `(topic, value)` to publish for this struct and every struct it contains,
+    /// nested structs first so each schema only refers to structs published before it
+    ///
+    /// The topic is `/.schema/struct:Name` and the value the utf-8 schema as `Raw`,
+    /// published with the [`FrcTypeString::StructSchema`] type
+    pub fn schema_closure(&self) -> Result<Vec<(String, FrcValue)>, FrcValueError> {
+        FrcOwnedStructDesc::from(self).schema_closure()
+    }
+
+    /// How data packed with this (older) description can be read with `new`,
+    /// see [`SchemaCompatibility`]
+    pub fn compatibility(&self, new: &FrcStructDesc) -> SchemaCompatibility {
+        if self == new {
+            return SchemaCompatibility::Identical;
+        }
+        compatibility(
+            (self.type_str, self.schema, self.size),
+            (new.type_str, new.schema, new.size),
+        )
+    }
+}
+
+/// The result of comparing two versions of a struct description field by field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaCompatibility {
+    /// Same fields with the same names, types, layout and enum variants
+    Identical,
+    /// Same layout, but some fields were renamed or their enum variants changed
+    LayoutCompatible,
+    /// Every old field is unchanged and new fields were only added after them,
+    /// old data is a prefix of new data
+    AppendOnly,
+    /// Fields were removed, moved or changed type, or either schema is invalid
+    Incompatible,
+}
+
+/// Compares the flattened fields of an old and a new `(type, schema, size)`
+fn compatibility(old: (&str, &str, usize), new: (&str, &str, usize)) -> SchemaCompatibility {
+    let (Ok(old), Ok(new)) = (
+        schema_fields(old.0, old.1, old.2),
+        schema_fields(new.0, new.1, new.2),
+    ) else {
+        return SchemaCompatibility::Incompatible;
+    };
+    let same_layout = |a: &SchemaField, b: &SchemaField| {
+        a.offset == b.offset && a.stype == b.stype && a.bits == b.bits
+    };
+    if old.len() == new.len() && old.iter().zip(&new).all(|(a, b)| same_layout(a, b)) {
+        if old == new {
+            SchemaCompatibility::Identical
+        } else {
+            SchemaCompatibility::LayoutCompatible
+        }
+    } else if old.len() < new.len() && old.iter().zip(&new).all(|(a, b)| a == b) {
+        SchemaCompatibility::AppendOnly
+    } else {
+        SchemaCompatibility::Incompatible
+    }
+}
+
+/// A struct description that owns its strings,
+/// for schemas only known at runtime (like ones read from a log or network tables)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FrcOwnedStructDesc {
+    pub schema: Cow<'static, str>,
+    pub type_str: Cow<'static, str>,
+    pub size: usize,
+}
+
+impl FrcOwnedStructDesc {
+    pub fn new(type_str: impl Into<String>, schema: impl Into<String>, size: usize) -> Self {
+        Self {
+            schema: Cow::Owned(schema.into()),
+            type_str: Cow::Owned(type_str.into()),
+            size,
+        }
+    }
+
+    /// A description with the size computed from the schema,
+    /// nested structs have to be registered in [`FrcStructDescDB`]
+    pub fn from_schema(
+        type_str: impl Into<String>,
+        schema: impl Into<String>,
+    ) -> Result<Self, SchemaError> {
+        let schema = schema.into();
+        let (_, size) = layout_schema(&schema, "", 0, None)?;
+        let desc = Self::new(type_str, schema, size);
+        desc.validate()?;
+        Ok(desc)
+    }
+
+    /// See [`FrcStructDesc::validate`]
+    pub fn validate(&self) -> Result<(), SchemaError> {
+        validate_schema(&self.type_str, &self.schema, self.size)
+    }
+
+    /// See [`schema_fingerprint`]
+    pub fn fingerprint(&self) -> u64 {
+        schema_fingerprint(&self.type_str, &self.schema)
+    }
+
+    /// See [`FrcStructDesc::compatibility`]
+    pub fn compatibility(&self, new: &FrcOwnedStructDesc) -> SchemaCompatibility {
+        compatibility(
+            (&self.type_str, &self.schema, self.size),
+            (&new.type_str, &new.schema, new.size),
+        )
+    }
+
+    /// See [`FrcStructDesc::schema_closure`]
+    pub fn schema_closure(&self) -> Result<Vec<(String, FrcValue)>, FrcValueError> {
+        Ok(dependency_order(std::slice::from_ref(self))?
+            .into_iter()
+            .map(|desc| {
+                let schema = match &desc.schema {
+                    Cow::Borrowed(schema) => Bytes::from_static(schema.as_bytes()),
+                    Cow::Owned(schema) => Bytes::copy_from_slice(schema.as_bytes()),
+                };
+                (
+                    format!(
+                        "/.schema/{}",
+                        FrcTypeString::Struct(desc.type_str.to_string())
+                    ),
+                    FrcValue::Raw(Box::new(schema)),
+                )
+            })
+            .collect())
+    }
+}
+
+/// The descriptions and every struct they contain (resolved through [`FrcStructDescDB`]),
+/// each struct once and after every struct it contains
+pub(crate) fn dependency_order(
+    descs: &[FrcOwnedStructDesc],
+) -> Result<Vec<Arc<FrcOwnedStructDesc>>, SchemaError> {
+    fn visit(
+        desc: Arc<FrcOwnedStructDesc>,
+        visiting: &mut Vec<Arc<FrcOwnedStructDesc>>,
+        out: &mut Vec<Arc<FrcOwnedStructDesc>>,
+    ) -> Result<(), SchemaError> {
+        if out.iter().any(|done| done.type_str == desc.type_str) {
+            return Ok(());
+        }
+        visiting.push(desc.clone());
+        for declaration in parse_declarations(&desc.schema)? {
+            if !declaration.is_struct {
+                continue;
+            }
+            let error = |reason| schema_error(&desc.schema, &declaration.type_span, reason);
+            if visiting
+                .iter()
+                .any(|desc| desc.type_str == declaration.type_name)
+            {
+                return Err(error(SchemaErrorReason::RecursiveStruct));
+            }
+            let nested = FrcStructDescDB::get_owned(declaration.type_name)
+                .ok_or_else(|| error(SchemaErrorReason::UnresolvedStruct))?;
+            visit(nested, visiting, out)?;
+        }
+        visiting.pop();
+        out.push(desc);
+        Ok(())
+    }
+    let mut out = Vec::new();
+    for desc in descs {
+        visit(Arc::new(desc.clone()), &mut Vec::new(), &mut out)?;
+    }
+    Ok(out)
+}
+
+impl From<&FrcStructDesc> for FrcOwnedStructDesc {
+    fn from(desc: &FrcStructDesc) -> Self {
+        Self {
+            schema: Cow::Borrowed(desc.schema),
+            type_str: Cow::Borrowed(desc.type_str),
+            size: desc.size,
+        }
+    }
+}
+
+/// The description a struct value is packed with,
+/// either static or registered at runtime with [`FrcStructDescDB::register`]
+#[derive(Debug, Clone)]
+pub enum FrcStructDescHandle {
+    Static(&'static FrcStructDesc),
+    Owned(Arc<FrcOwnedStructDesc>),
+}
+
+impl FrcStructDescHandle {
+    pub fn type_str(&self) -> &str {
+        match self {
+            Self::Static(desc) => desc.type_str,
+            Self::Owned(desc) => &desc.type_str,
+        }
+    }
+
+    pub fn schema(&self) -> &str {
+        match self {
+            Self::Static(desc) => desc.schema,
+            Self::Owned(desc) => &desc.schema,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Static(desc) => desc.size,
+            Self::Owned(desc) => desc.size,
+        }
+    }
+
+    /// The static description, `None` for owned descriptions
+    pub fn as_static(&self) -> Option<&'static FrcStructDesc> {
+        match self {
+            Self::Static(desc) => Some(desc),
+            Self::Owned(_) => None,
+        }
+    }
+
+    /// See [`FrcStructDesc::type_string`]
+    pub fn type_string(&self) -> FrcTypeString {
+        FrcTypeString::Struct(self.type_str().to_owned())
+    }
+
+    /// See [`FrcStructDesc::array_type_string`]
+    pub fn array_type_string(&self) -> FrcTypeString {
+        FrcTypeString::StructArray(self.type_str().to_owned())
+    }
+
+    /// See [`schema_fingerprint`]
+    pub fn fingerprint(&self) -> u64 {
+        match self {
+            Self::Static(desc) => desc.fingerprint(),
+            Self::Owned(desc) => desc.fingerprint(),
+        }
+    }
+
+    /// The flattened fields of the schema, validated against the size
+    pub(crate) fn fields(&self) -> Result<Vec<SchemaField>, SchemaError> {
+        schema_fields(self.type_str(), self.schema(), self.size())
+    }
+
+    /// The type name for errors, which can only name static descriptions
+    pub(crate) fn error_name(&self) -> &'static str {
+        match self {
+            Self::Static(desc) => desc.type_str,
+            Self::Owned(_) => FrcType::Struct.name(),
+        }
+    }
+}
+
+impl PartialEq for FrcStructDescHandle {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_str() == other.type_str()
+            && self.schema() == other.schema()
+            && self.size() == other.size()
+    }
+}
+
+impl Eq for FrcStructDescHandle {}
+
+impl From<&'static FrcStructDesc> for FrcStructDescHandle {
+    fn from(desc: &'static FrcStructDesc) -> Self {
+        Self::Static(desc)
+    }
+}
+
+impl From<Arc<FrcOwnedStructDesc>> for FrcStructDescHandle {
+    fn from(desc: Arc<FrcOwnedStructDesc>) -> Self {
+        Self::Owned(desc)
+    }
+}
+
+impl From<FrcOwnedStructDesc> for FrcStructDescHandle {
+    fn from(desc: FrcOwnedStructDesc) -> Self {
+        Self::Owned(Arc::new(desc))
+    }
+}
+
+/// Checks the schema of the `type_str` struct adds up to `size`
+/// and every struct it contains is registered and doesn't contain it,
+/// without expanding (arrays of) nested structs into their fields
+fn validate_schema(type_str: &str, schema: &str, size: usize) -> Result<(), SchemaError> {
+    dependency_order(&[FrcOwnedStructDesc::new(type_str, schema, size)])?;
+    let (_, actual) = layout_schema(schema, "", 0, None)?;
+    if actual != size {
+        return Err(size_mismatch(schema, size, actual));
+    }
+    Ok(())
+}
+
+/// Parses the schema of the `type_str` struct and checks its fields add up to `size`
+fn schema_fields(
+    type_str: &str,
+    schema: &str,
+    size: usize,
+) -> Result<Vec<SchemaField>, SchemaError> {
+    // validating first bounds the fields by the size instead of the array counts
+    validate_schema(type_str, schema, size)?;
+    let (fields, _) = layout_schema(schema, "", 0, Some(&mut vec![type_str.to_owned()]))?;
+    let actual = fields_size(&fields);
+    if actual != size {
+        return Err(size_mismatch(schema, size, actual));
+    }
+    Ok(fields)
+}
+
+fn size_mismatch(schema: &str, expected: usize, actual: usize) -> SchemaError {
+    SchemaError {
+        position: schema.len(),
+        token: String::new(),
+        reason: SchemaErrorReason::SizeMismatch { expected, actual },
+    }
+}
+
+/// The size of a struct made of the fields
+fn fields_size(fields: &[SchemaField]) -> usize {
+    fields
+        .iter()
+        .map(|field| field.offset + field.bits.map_or(field.stype.size(), |bits| bits.storage))
+        .max()
+        .unwrap_or_default()
+}
+
 inventory::collect!(FrcStructDesc);
 
+/// Descriptions indexed by name and by [`schema_fingerprint`]
+struct DescIndex<N, D> {
+    by_name: HashMap<N, (D, u64), fxhash::FxBuildHasher>,
+    by_fingerprint: HashMap<u64, D, fxhash::FxBuildHasher>,
+}
+
+impl<N, D> Default for DescIndex<N, D> {
+    fn default() -> Self {
+        Self {
+            by_name: HashMap::default(),
+            by_fingerprint: HashMap::default(),
+        }
+    }
+}
+
+impl<N: Hash + Eq + Borrow<str>, D: Clone> DescIndex<N, D> {
+    fn insert(&mut self, name: N, desc: D, fingerprint: u64) -> Option<D> {
+        let replaced = self.remove(name.borrow());
+        self.by_fingerprint.insert(fingerprint, desc.clone());
+        self.by_name.insert(name, (desc, fingerprint));
+        replaced
+    }
+
+    fn remove(&mut self, name: &str) -> Option<D> {
+        let (desc, fingerprint) = self.by_name.remove(name)?;
+        self.by_fingerprint.remove(&fingerprint);
+        Some(desc)
+    }
+}
+
+type StaticDescs = DescIndex<&'static str, &'static FrcStructDesc>;
+type OwnedDescs = DescIndex<String, Arc<FrcOwnedStructDesc>>;
+
+static STATIC_DESCS: OnceLock<RwLock<StaticDescs>> = OnceLock::new();
+static OWNED_DESCS: OnceLock<RwLock<OwnedDescs>> = OnceLock::new();
+
+/// The `inventory` entries indexed by name, built on first use
+fn static_descs() -> &'static RwLock<StaticDescs> {
+    STATIC_DESCS.get_or_init(|| {
+        let mut descs = StaticDescs::default();
+        for desc in inventory::iter::<FrcStructDesc> {
+            if !descs.by_name.contains_key(desc.type_str) {
+                let fingerprint = schema_fingerprint(desc.type_str, desc.schema);
+                descs.insert(desc.type_str, desc, fingerprint);
+            }
+        }
+        RwLock::new(descs)
+    })
+}
+
+fn owned_descs() -> &'static RwLock<OwnedDescs> {
+    OWNED_DESCS.get_or_init(Default::default)
+}
+
+/// A hash of the type name and [canonical schema](normalize_schema) that is stable across runs,
+/// platforms and compiler versions (64 bit FNV-1a)
+///
+/// Invalid schemas are hashed as written
+pub fn schema_fingerprint(type_str: &str, schema: &str) -> u64 {
+    let schema = normalize_schema(schema).unwrap_or_else(|_| schema.to_owned());
+    let mut hash = 0xcbf29ce484222325u64;
+    for byte in type_str.bytes().chain([0]).chain(schema.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// The registry of struct descriptions
+///
+/// Static descriptions (submitted through `inventory` by the derive or added with [`FrcStructDescDB::add`])
+/// live for the whole program, owned descriptions can be registered, replaced and removed at runtime.
+/// When both exist for a name the owned description is used to resolve nested structs
 pub struct FrcStructDescDB;
 
 impl FrcStructDescDB {
-    ///Call very sparringly as this function leaks memory
-    pub fn add(desc: FrcStructDesc) {
-        if Self::contains(desc.type_str) {
-            return;
+    ///Call very sparringly as this function leaks memory,
+    ///use [`FrcStructDescDB::register`] for descriptions that aren't known at compile time
+    ///
+    ///Re-registering a name keeps the first description,
+    ///this is an error unless both descriptions are identical
+    ///
+    ///Descriptions that don't [validate](FrcStructDesc::validate) are rejected
+    pub fn add(desc: FrcStructDesc) -> Result<(), FrcValueError> {
+        match Self::get(desc.type_str) {
+            Some(existing) => Self::check_conflict(existing, &desc),
+            None => Self::add_ref(Box::leak(Box::new(desc))),
         }
-        let static_desc_ref = Box::leak(Box::new(desc));
-        let node = inventory::Node {
-            value: static_desc_ref,
-            next: ::inventory::core::cell::UnsafeCell::new(::inventory::core::option::Option::None),
-        };
-        unsafe { inventory::ErasedNode::submit(node.value, Box::leak(Box::new(node))) }
     }
 
-    ///Call very sparringly as this function leaks memory
-    pub fn add_ref(desc: &'static FrcStructDesc) {
-        if Self::contains(desc.type_str) {
-            return;
+    ///Re-registering a name keeps the first description,
+    ///this is an error unless both descriptions are identical
+    ///
+    ///Descriptions that don't [validate](FrcStructDesc::validate) are rejected
+    pub fn add_ref(desc: &'static FrcStructDesc) -> Result<(), FrcValueError> {
+        desc.validate()?;
+        let fingerprint = schema_fingerprint(desc.type_str, desc.schema);
+        let mut descs = static_descs()
+            .write()
+            .expect("FrcStructDescDB static descriptions poisoned");
+        match descs.by_name.get(desc.type_str) {
+            Some((existing, _)) => Self::check_conflict(existing, desc),
+            None => {
+                descs.insert(desc.type_str, desc, fingerprint);
+                Ok(())
+            }
+        }
+    }
+
+    fn check_conflict(existing: &FrcStructDesc, desc: &FrcStructDesc) -> Result<(), FrcValueError> {
+        match existing.compatibility(desc) {
+            SchemaCompatibility::Identical => Ok(()),
+            compatibility => Err(FrcValueError::SchemaConflict(
+                desc.type_str.to_owned(),
+                compatibility,
+            )),
         }
-        let node = inventory::Node {
-            value: desc,
-            next: ::inventory::core::cell::UnsafeCell::new(::inventory::core::option::Option::None),
-        };
-        unsafe { inventory::ErasedNode::submit(node.value, Box::leak(Box::new(node))) }
     }
 
+    /// Registers an owned description, returns the description it replaced
+    ///
+    /// Descriptions that don't [validate](FrcOwnedStructDesc::validate) are rejected,
+    /// so values of registered structs can always be decoded
+    pub fn register(
+        desc: FrcOwnedStructDesc,
+    ) -> Result<Option<Arc<FrcOwnedStructDesc>>, FrcValueError> {
+        desc.validate()?;
+        let fingerprint = desc.fingerprint();
+        Ok(owned_descs()
+            .write()
+            .expect("FrcStructDescDB owned descriptions poisoned")
+            .insert(
+                desc.type_str.clone().into_owned(),
+                Arc::new(desc),
+                fingerprint,
+            ))
+    }
+
+    /// Removes an owned description, static descriptions can't be removed
+    pub fn remove(type_str: &str) -> Option<Arc<FrcOwnedStructDesc>> {
+        owned_descs()
+            .write()
+            .expect("FrcStructDescDB owned descriptions poisoned")
+            .remove(type_str)
+    }
+
+    /// If a static or owned description is registered for the name
     pub fn contains(type_str: &str) -> bool {
-        inventory::iter::<FrcStructDesc>
-            .into_iter()
-            .any(|desc| desc.type_str == type_str)
+        Self::get(type_str).is_some()
+            || owned_descs()
+                .read()
+                .expect("FrcStructDescDB owned descriptions poisoned")
+                .by_name
+                .contains_key(type_str)
     }
 
+    /// The static description registered for the name
     pub fn get(type_str: &str) -> Option<&'static FrcStructDesc> {
-        inventory::iter::<FrcStructDesc>
-            .into_iter()
-            .find(|desc| desc.type_str == type_str)
+        static_descs()
+            .read()
+            .expect("FrcStructDescDB static descriptions poisoned")
+            .by_name
+            .get(type_str)
+            .map(|(desc, _)| *desc)
+    }
+
+    /// The static description with the [`schema_fingerprint`]
+    pub fn get_by_fingerprint(fingerprint: u64) -> Option<&'static FrcStructDesc> {
+        static_descs()
+            .read()
+            .expect("FrcStructDescDB static descriptions poisoned")
+            .by_fingerprint
+            .get(&fingerprint)
+            .copied()
+    }
+
+    /// The description values of the named struct are decoded with,
+    /// the owned description if one is registered (like [`FrcStructDescDB::get_owned`])
+    /// and the static description otherwise
+    pub fn get_handle(type_str: &str) -> Option<FrcStructDescHandle> {
+        let owned = owned_descs()
+            .read()
+            .expect("FrcStructDescDB owned descriptions poisoned")
+            .by_name
+            .get(type_str)
+            .map(|(desc, _)| FrcStructDescHandle::Owned(desc.clone()));
+        owned.or_else(|| Self::get(type_str).map(FrcStructDescHandle::Static))
+    }
+
+    /// The owned description registered for the name,
+    /// falls back to a borrowing copy of the static description
+    pub fn get_owned(type_str: &str) -> Option<Arc<FrcOwnedStructDesc>> {
+        let owned = owned_descs()
+            .read()
+            .expect("FrcStructDescDB owned descriptions poisoned")
+            .by_name
+            .get(type_str)
+            .map(|(desc, _)| desc.clone());
+        owned.or_else(|| Self::get(type_str).map(|desc| Arc::new(desc.into())))
+    }
+
+    /// The owned description with the [`schema_fingerprint`],
+    /// falls back to a borrowing copy of the static description
+    pub fn get_owned_by_fingerprint(fingerprint: u64) -> Option<Arc<FrcOwnedStructDesc>> {
+        let owned = owned_descs()
+            .read()
+            .expect("FrcStructDescDB owned descriptions poisoned")
+            .by_fingerprint
+            .get(&fingerprint)
+            .cloned();
+        owned.or_else(|| Self::get_by_fingerprint(fingerprint).map(|desc| Arc::new(desc.into())))
     }
 }
 
@@ -68,31 +601,205 @@ where
         size: Self::SIZE,
     };
 
+    /// The inline `enum {A=0, B=1} uint8` declaration derived schemas use
+    /// instead of the type name for fields of this type, `None` for structures
+    const ENUM_SPEC: Option<&'static str> = None;
+
     fn pack(&self, buffer: &mut impl BufMut);
 
+    /// May panic if [`FrcStructure::is_valid`] is false for the buffer
     fn unpack(buffer: &mut impl Buf) -> Self;
+
+    /// Whether the first `SIZE` bytes of `buffer` can be unpacked,
+    /// only enums (and structures containing them) reject values that don't match a variant
+    fn is_valid(buffer: &[u8]) -> bool {
+        buffer.len() >= Self::SIZE
+    }
+}
+
+/// Support for the `FrcStructure` derive, not public api
+#[doc(hidden)]
+pub mod __derive {
+    use super::{FrcStructDesc, FrcStructure};
+
+    /// The schema type of a `T` field, its inline enum declaration or its type name
+    pub const fn field_type<T: FrcStructure>() -> &'static str {
+        match T::ENUM_SPEC {
+            Some(spec) => spec,
+            None => T::TYPE,
+        }
+    }
+
+    /// The description registered for a `T` field of `parent`,
+    /// enums are declared inline so `parent` is registered again in their place
+    pub const fn field_desc<T: FrcStructure>(parent: FrcStructDesc) -> FrcStructDesc {
+        match T::ENUM_SPEC {
+            Some(_) => parent,
+            None => T::DESCRIPTION,
+        }
+    }
+
+    /// The combined length of the parts
+    pub const fn concat_len(parts: &[&str]) -> usize {
+        let mut len = 0;
+        let mut i = 0;
+        while i < parts.len() {
+            len += parts[i].len();
+            i += 1;
+        }
+        len
+    }
+
+    /// A string concatenated at compile time,
+    /// derived schemas name nested structs by their `FrcStructure::TYPE` through this
+    pub struct ConstStr<const N: usize> {
+        bytes: [u8; N],
+        len: usize,
+    }
+
+    impl<const N: usize> ConstStr<N> {
+        pub const fn concat(parts: &[&str]) -> Self {
+            let mut bytes = [0u8; N];
+            let mut len = 0;
+            let mut i = 0;
+            while i < parts.len() {
+                let part = parts[i].as_bytes();
+                assert!(
+                    len + part.len() <= N,
+                    "derived FrcStructure schema is too long"
+                );
+                let mut j = 0;
+                while j < part.len() {
+                    bytes[len] = part[j];
+                    len += 1;
+                    j += 1;
+                }
+                i += 1;
+            }
+            Self { bytes, len }
+        }
+
+        /// The decimal digits of the number
+        pub const fn number(mut number: usize) -> Self {
+            let mut bytes = [0u8; N];
+            let mut len = 0;
+            let mut divisor = 1;
+            while number / divisor >= 10 {
+                divisor *= 10;
+            }
+            while divisor > 0 {
+                assert!(len < N, "derived FrcStructure schema is too long");
+                bytes[len] = b'0' + (number / divisor) as u8;
+                number %= divisor;
+                divisor /= 10;
+                len += 1;
+            }
+            Self { bytes, len }
+        }
+
+        pub const fn as_str(&self) -> &str {
+            match std::str::from_utf8(self.bytes.split_at(self.len).0) {
+                Ok(string) => string,
+                Err(_) => panic!("derived FrcStructure schema is not utf-8"),
+            }
+        }
+    }
 }
 
 impl FrcValue {
     pub fn from_struct<T: FrcStructure>(value: T) -> Self {
         let mut buffer = BytesMut::with_capacity(T::SIZE);
         value.pack(&mut buffer);
-        Self::Struct(&T::DESCRIPTION, Box::new(buffer.freeze()))
+        Self::Struct(
+            FrcStructDescHandle::Static(&T::DESCRIPTION),
+            Box::new(buffer.freeze()),
+        )
     }
 
     pub fn try_into_struct<T: FrcStructure>(self) -> Result<T, FrcValueError> {
-        let frc_type = self.get_type();
+        self.as_struct()
+    }
+
+    /// Unpacks the struct without consuming or cloning the value, fails if the value
+    /// was packed from a different struct type or holds an unknown enum value
+    pub fn as_struct<T: FrcStructure>(&self) -> Result<T, FrcValueError> {
         match self {
-            Self::Struct(_, mut buffer) => {
-                if buffer.len() == T::SIZE {
-                    Ok(T::unpack(&mut *buffer))
-                } else {
-                    Err(FrcValueError::InvalidCast(
-                        frc_type,
-                        T::TYPE,
-                        CastErrorReason::Type,
-                    ))
+            Self::Struct(desc, buffer)
+                if is_desc_of::<T>(desc) && buffer.len() == T::SIZE && T::is_valid(buffer) =>
+            {
+                Ok(T::unpack(&mut &buffer[..]))
+            }
+            _ => Err(FrcValueError::InvalidCast(
+                self.get_type(),
+                T::TYPE,
+                CastErrorReason::Type,
+            )),
+        }
+    }
+}
+
+/// Whether `desc` describes the same struct as `T`
+fn is_desc_of<T: FrcStructure>(desc: &FrcStructDescHandle) -> bool {
+    desc.type_str() == T::TYPE && desc.size() == T::SIZE
+}
+
+impl FrcValue {
+    /// Creates a struct array from already packed instances,
+    /// fails if the length of `buffer` isn't a multiple of `desc.size`
+    pub fn try_struct_array(
+        desc: impl Into<FrcStructDescHandle>,
+        buffer: Bytes,
+    ) -> Result<Self, FrcValueError> {
+        let desc = desc.into();
+        let valid = match desc.size() {
+            0 => buffer.is_empty(),
+            size => buffer.len().is_multiple_of(size),
+        };
+        if !valid {
+            return Err(FrcValueError::InvalidCast(
+                crate::FrcType::Raw,
+                desc.error_name(),
+                CastErrorReason::Type,
+            ));
+        }
+        Ok(Self::StructArray(desc, Box::new(buffer)))
+    }
+
+    pub fn from_struct_slice<T: FrcStructure>(values: &[T]) -> Self {
+        let mut buffer = BytesMut::with_capacity(T::SIZE * values.len());
+        for value in values {
+            value.pack(&mut buffer);
+        }
+        Self::StructArray(
+            FrcStructDescHandle::Static(&T::DESCRIPTION),
+            Box::new(buffer.freeze()),
+        )
+    }
+
+    pub fn try_into_struct_vec<T: FrcStructure>(self) -> Result<Vec<T>, FrcValueError> {
+        self.as_struct_vec()
+    }
+
+    /// Unpacks every struct of the array without consuming or cloning the value
+    pub fn as_struct_vec<T: FrcStructure>(&self) -> Result<Vec<T>, FrcValueError> {
+        match self {
+            Self::StructArray(desc, buffer)
+                if is_desc_of::<T>(desc) && T::SIZE == 0 && buffer.is_empty() =>
+            {
+                Ok(Vec::new())
+            }
+            Self::StructArray(desc, buffer)
+                if is_desc_of::<T>(desc)
+                    && T::SIZE != 0
+                    && buffer.len().is_multiple_of(T::SIZE)
+                    && buffer.chunks(T::SIZE).all(T::is_valid) =>
+            {
+                let mut data = &buffer[..];
+                let mut values = Vec::with_capacity(buffer.len() / T::SIZE);
+                while !data.is_empty() {
+                    values.push(T::unpack(&mut data));
                 }
+                Ok(values)
             }
             _ => Err(FrcValueError::InvalidCast(
                 self.get_type(),
@@ -101,6 +808,15 @@ impl FrcValue {
             )),
         }
     }
+
+    /// The number of structs in a struct array
+    pub fn struct_array_len(&self) -> Option<usize> {
+        match self {
+            Self::StructArray(desc, buffer) if desc.size() != 0 => Some(buffer.len() / desc.size()),
+            Self::StructArray(_, _) => Some(0),
+            _ => None,
+        }
+    }
 }
 
 impl<T: FrcStructure> From<T> for FrcValue {
@@ -164,6 +880,212 @@ impl StructureFieldTypes {
         self.base_size() * self.count()
     }
 
+    fn is_numeric(&self) -> bool {
+        !matches!(self, Self::Bool(_) | Self::Char(_))
+    }
+
+    fn is_integer(&self) -> bool {
+        self.is_numeric() && !matches!(self, Self::Float32(_) | Self::Float64(_))
+    }
+
+    fn is_signed(&self) -> bool {
+        matches!(
+            self,
+            Self::Int8(_) | Self::Int16(_) | Self::Int32(_) | Self::Int64(_)
+        )
+    }
+
+    /// Linearly interpolates a single element of this field type,
+    /// integers are rounded to the nearest value
+    fn lerp_element(&self, mut a: &[u8], mut b: &[u8], t: f64, out: &mut BytesMut) {
+        fn lerp_int(a: i128, b: i128, t: f64) -> i128 {
+            a + ((b - a) as f64 * t).round() as i128
+        }
+        fn lerp_float(a: f64, b: f64, t: f64) -> f64 {
+            a + (b - a) * t
+        }
+        match self {
+            Self::Int8(_) => out.put_i8(lerp_int(a.get_i8() as i128, b.get_i8() as i128, t) as i8),
+            Self::Int16(_) => {
+                out.put_i16_le(lerp_int(a.get_i16_le() as i128, b.get_i16_le() as i128, t) as i16)
+            }
+            Self::Int32(_) => {
+                out.put_i32_le(lerp_int(a.get_i32_le() as i128, b.get_i32_le() as i128, t) as i32)
+            }
+            Self::Int64(_) => {
+                out.put_i64_le(lerp_int(a.get_i64_le() as i128, b.get_i64_le() as i128, t) as i64)
+            }
+            Self::UInt8(_) => out.put_u8(lerp_int(a.get_u8() as i128, b.get_u8() as i128, t) as u8),
+            Self::UInt16(_) => {
+                out.put_u16_le(lerp_int(a.get_u16_le() as i128, b.get_u16_le() as i128, t) as u16)
+            }
+            Self::UInt32(_) => {
+                out.put_u32_le(lerp_int(a.get_u32_le() as i128, b.get_u32_le() as i128, t) as u32)
+            }
+            Self::UInt64(_) => {
+                out.put_u64_le(lerp_int(a.get_u64_le() as i128, b.get_u64_le() as i128, t) as u64)
+            }
+            Self::Float32(_) => {
+                out.put_f32_le(lerp_float(a.get_f32_le() as f64, b.get_f32_le() as f64, t) as f32)
+            }
+            Self::Float64(_) => out.put_f64_le(lerp_float(a.get_f64_le(), b.get_f64_le(), t)),
+            Self::Bool(_) | Self::Char(_) => {
+                unreachable!("non numeric struct fields can't be interpolated")
+            }
+        }
+    }
+
+    /// The schema name of the element type
+    fn type_name(&self) -> &'static str {
+        match self {
+            Self::Bool(_) => "bool",
+            Self::Char(_) => "char",
+            Self::Int8(_) => "int8",
+            Self::Int16(_) => "int16",
+            Self::Int32(_) => "int32",
+            Self::Int64(_) => "int64",
+            Self::UInt8(_) => "uint8",
+            Self::UInt16(_) => "uint16",
+            Self::UInt32(_) => "uint32",
+            Self::UInt64(_) => "uint64",
+            Self::Float32(_) => "float32",
+            Self::Float64(_) => "float64",
+        }
+    }
+
+    /// Reads a single element, integers are widened to `Int` and chars become a one character `String`,
+    /// `uint64` values above `i64::MAX` are read as the nearest `Double`
+    fn read_element(&self, mut buffer: &[u8]) -> FrcValue {
+        match self {
+            Self::Bool(_) => FrcValue::Boolean(buffer.get_u8() != 0),
+            Self::Char(_) => FrcValue::String((buffer.get_u8() as char).to_string()),
+            Self::Int8(_) => FrcValue::Int(buffer.get_i8() as i64),
+            Self::Int16(_) => FrcValue::Int(buffer.get_i16_le() as i64),
+            Self::Int32(_) => FrcValue::Int(buffer.get_i32_le() as i64),
+            Self::Int64(_) => FrcValue::Int(buffer.get_i64_le()),
+            Self::UInt8(_) => FrcValue::Int(buffer.get_u8() as i64),
+            Self::UInt16(_) => FrcValue::Int(buffer.get_u16_le() as i64),
+            Self::UInt32(_) => FrcValue::Int(buffer.get_u32_le() as i64),
+            Self::UInt64(_) => uint(buffer.get_u64_le()),
+            Self::Float32(_) => FrcValue::Float(buffer.get_f32_le()),
+            Self::Float64(_) => FrcValue::Double(buffer.get_f64_le()),
+        }
+    }
+
+    /// Reads the whole field, arrays become the matching array variant
+    /// and char arrays a `String` cut at the first null byte
+    ///
+    /// `uint64` arrays with values above `i64::MAX` are read as a `DoubleArray`
+    fn read(&self, buffer: &[u8]) -> FrcValue {
+        let count = self.count();
+        if count == 1 {
+            return self.read_element(buffer);
+        }
+        let elements = buffer.chunks_exact(self.base_size()).take(count);
+        match self {
+            Self::Bool(_) => FrcValue::BooleanArray(elements.map(|e| e[0] != 0).collect()),
+            Self::Char(_) => {
+                let end = buffer[..count]
+                    .iter()
+                    .position(|c| *c == 0)
+                    .unwrap_or(count);
+                FrcValue::String(String::from_utf8_lossy(&buffer[..end]).into_owned())
+            }
+            Self::Float32(_) => FrcValue::FloatArray(
+                elements
+                    .filter_map(|e| self.read_element(e).as_f32())
+                    .collect(),
+            ),
+            Self::Float64(_) => FrcValue::DoubleArray(
+                elements
+                    .filter_map(|e| self.read_element(e).as_f64())
+                    .collect(),
+            ),
+            Self::UInt64(_) => {
+                let values = elements.map(|mut e| e.get_u64_le()).collect::<Vec<_>>();
+                if values.iter().all(|v| i64::try_from(*v).is_ok()) {
+                    FrcValue::IntArray(values.into_iter().map(|v| v as i64).collect())
+                } else {
+                    FrcValue::DoubleArray(values.into_iter().map(|v| v as f64).collect())
+                }
+            }
+            _ => FrcValue::IntArray(
+                elements
+                    .filter_map(|e| self.read_element(e).as_i64())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Writes a single element, numbers are converted to the field type if they fit
+    fn write_element(&self, value: &FrcValue, mut out: &mut [u8]) -> Result<(), CastErrorReason> {
+        fn int<T: TryFrom<i64>>(v: i64) -> Result<T, CastErrorReason> {
+            T::try_from(v).map_err(|_| {
+                if v < 0 {
+                    CastErrorReason::Underflow
+                } else {
+                    CastErrorReason::Overflow
+                }
+            })
+        }
+        let read_int = || value.as_i64_exact().map_err(|_| CastErrorReason::Type);
+        let read_float = || value.as_f64_lossy().map_err(|_| CastErrorReason::Type);
+        match self {
+            Self::Bool(_) => out.put_u8(value.as_bool().ok_or(CastErrorReason::Type)? as u8),
+            Self::Char(_) => match value.as_str().map(str::as_bytes) {
+                Some([c]) => out.put_u8(*c),
+                _ => return Err(CastErrorReason::Type),
+            },
+            Self::Int8(_) => out.put_i8(int(read_int()?)?),
+            Self::Int16(_) => out.put_i16_le(int(read_int()?)?),
+            Self::Int32(_) => out.put_i32_le(int(read_int()?)?),
+            Self::Int64(_) => out.put_i64_le(read_int()?),
+            Self::UInt8(_) => out.put_u8(int(read_int()?)?),
+            Self::UInt16(_) => out.put_u16_le(int(read_int()?)?),
+            Self::UInt32(_) => out.put_u32_le(int(read_int()?)?),
+            Self::UInt64(_) => out.put_u64_le(int(read_int()?)?),
+            Self::Float32(_) => out.put_f32_le(read_float()? as f32),
+            Self::Float64(_) => out.put_f64_le(read_float()?),
+        }
+        Ok(())
+    }
+
+    /// Writes the whole field, arrays have to be the same length as the field
+    /// and strings for char arrays are null padded
+    fn write(&self, value: &FrcValue, out: &mut [u8]) -> Result<(), CastErrorReason> {
+        let count = self.count();
+        if count == 1 {
+            return self.write_element(value, out);
+        }
+        let elements: Vec<FrcValue> = match (self, value) {
+            (Self::Char(_), FrcValue::String(s)) => {
+                if s.len() > count {
+                    return Err(CastErrorReason::Overflow);
+                }
+                out[..count].fill(0);
+                out[..s.len()].copy_from_slice(s.as_bytes());
+                return Ok(());
+            }
+            (_, FrcValue::BooleanArray(v)) => v.iter().map(|v| FrcValue::Boolean(*v)).collect(),
+            (_, FrcValue::IntArray(v)) => v.iter().map(|v| FrcValue::Int(*v)).collect(),
+            (_, FrcValue::FloatArray(v)) => v.iter().map(|v| FrcValue::Float(*v)).collect(),
+            (_, FrcValue::DoubleArray(v)) => v.iter().map(|v| FrcValue::Double(*v)).collect(),
+            _ => return Err(CastErrorReason::Type),
+        };
+        if elements.len() != count {
+            return Err(CastErrorReason::Type);
+        }
+        let mut scratch = vec![0u8; self.size()];
+        for (element, out) in elements
+            .iter()
+            .zip(scratch.chunks_exact_mut(self.base_size()))
+        {
+            self.write_element(element, out)?;
+        }
+        out[..scratch.len()].copy_from_slice(&scratch);
+        Ok(())
+    }
+
     fn from_type(type_name: &str, count: usize) -> Option<Self> {
         match type_name {
             "bool" => Some(Self::Bool(count)),
@@ -216,10 +1138,10 @@ pub(crate) enum Token<'a> {
             let split = lex.slice().split("=").collect::<Vec<_>>();
             Ok::<_, LexingError>((
                 *split.get(0).ok_or(LexingError::EnumVariantError)?,
-                split.get(1).ok_or(LexingError::EnumVariantError)?.parse::<i8>()?
+                split.get(1).ok_or(LexingError::EnumVariantError)?.parse::<i64>()?
             ))
         }, priority = 3)]
-    EnumVariant((&'a str, i8)),
+    EnumVariant((&'a str, i64)),
 
     #[regex(r"-?[0-9]+", |lex| lex.slice().parse(), priority = 2)]
     Integer(i64),
@@ -243,105 +1165,843 @@ pub(crate) enum Token<'a> {
     Colon,
 }
 
+/// A single primitive field of a schema, nested structs are flattened into their fields
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SchemaField {
+    pub name: String,
+    pub offset: usize,
+    pub stype: StructureFieldTypes,
+    /// The variant names and values of an integer field declared with `enum {a=1, b=2}`
+    pub variants: Option<Vec<(String, i64)>>,
+    /// Set for bit-fields declared like `int32 a:4`, `offset` is the offset of the storage unit
+    pub bits: Option<Bitfield>,
+}
+
+impl SchemaField {
+    /// Reads the field (or a single element of it) from a buffer starting at its offset
+    fn read(&self, buffer: &[u8], element: bool) -> FrcValue {
+        match self.bits {
+            Some(bits) => bits.read(self.stype, buffer),
+            None if element => self.stype.read_element(buffer),
+            None => self.stype.read(buffer),
+        }
+    }
+
+    /// Writes the field (or a single element of it) to a buffer starting at its offset
+    fn write(
+        &self,
+        value: &FrcValue,
+        buffer: &mut [u8],
+        element: bool,
+    ) -> Result<(), CastErrorReason> {
+        match self.bits {
+            Some(bits) => bits.write(self.stype, value, buffer),
+            None if element => self.stype.write_element(value, buffer),
+            None => self.stype.write(value, buffer),
+        }
+    }
+}
+
+/// An unsigned value as an `Int`, or the nearest `Double` if it's above `i64::MAX`
+fn uint(value: u64) -> FrcValue {
+    i64::try_from(value).map_or(FrcValue::Double(value as f64), FrcValue::Int)
+}
+
+/// The position of a bit-field inside its little endian storage unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Bitfield {
+    /// size of the storage unit in bytes
+    pub storage: usize,
+    pub shift: u32,
+    pub width: u32,
+}
+
+impl Bitfield {
+    fn mask(&self) -> u64 {
+        u64::MAX >> (64 - self.width)
+    }
+
+    /// Reads the field, signed fields are sign extended
+    fn read(&self, stype: StructureFieldTypes, mut buffer: &[u8]) -> FrcValue {
+        let raw = (buffer.get_uint_le(self.storage) >> self.shift) & self.mask();
+        match stype {
+            StructureFieldTypes::Bool(_) => FrcValue::Boolean(raw != 0),
+            StructureFieldTypes::Int8(_)
+            | StructureFieldTypes::Int16(_)
+            | StructureFieldTypes::Int32(_)
+            | StructureFieldTypes::Int64(_) => {
+                let unused = 64 - self.width;
+                FrcValue::Int(((raw << unused) as i64) >> unused)
+            }
+            _ => uint(raw),
+        }
+    }
+
+    /// Writes the field leaving the other bits of the storage unit untouched
+    fn write(
+        &self,
+        stype: StructureFieldTypes,
+        value: &FrcValue,
+        buffer: &mut [u8],
+    ) -> Result<(), CastErrorReason> {
+        let raw = match stype {
+            StructureFieldTypes::Bool(_) => value.as_bool().ok_or(CastErrorReason::Type)? as u64,
+            _ => {
+                let v = value.as_i64_exact().map_err(|_| CastErrorReason::Type)?;
+                let (min, max) = if stype.is_signed() {
+                    (
+                        -(1i128 << (self.width - 1)),
+                        (1i128 << (self.width - 1)) - 1,
+                    )
+                } else {
+                    (0, (1i128 << self.width) - 1)
+                };
+                if (v as i128) < min {
+                    return Err(CastErrorReason::Underflow);
+                } else if (v as i128) > max {
+                    return Err(CastErrorReason::Overflow);
+                }
+                v as u64 & self.mask()
+            }
+        };
+        let storage = (&buffer[..self.storage]).get_uint_le(self.storage);
+        let storage = storage & !(self.mask() << self.shift) | raw << self.shift;
+        (&mut buffer[..self.storage]).put_uint_le(storage, self.storage);
+        Ok(())
+    }
+}
+
+/// The storage unit bit-fields are currently being packed into
+struct BitfieldUnit {
+    offset: usize,
+    storage: usize,
+    used: u32,
+}
+
+/// The `(path, offset, type)` view of [`parse_schema_fields`]
+#[cfg(test)]
 pub(crate) fn parse_schema_toplevel(
     schema: &'static str,
-) -> Vec<(String, usize, StructureFieldTypes)> {
+) -> Result<Vec<(String, usize, StructureFieldTypes)>, SchemaError> {
     parse_schema(schema, "", 0)
 }
 
+#[cfg(test)]
 pub(crate) fn parse_schema(
     schema: &'static str,
     prefix: &str,
     offset: usize,
-) -> Vec<(String, usize, StructureFieldTypes)> {
-    let lexer = Token::lexer(schema);
-    let tokens_collect: Vec<_> = lexer.collect();
-    for tok in &tokens_collect {
-        if tok.is_err() {
-            return vec![];
+) -> Result<Vec<(String, usize, StructureFieldTypes)>, SchemaError> {
+    Ok(parse_schema_fields(schema, prefix, offset)?
+        .into_iter()
+        .map(|field| (field.name, field.offset, field.stype))
+        .collect())
+}
+
+type Span = std::ops::Range<usize>;
+
+/// A single `[enum {..}] type name[count]` or `type name:bits` declaration of a schema
+pub(crate) struct Declaration<'a> {
+    pub variants: Option<Vec<(&'a str, i64)>>,
+    pub type_name: &'a str,
+    pub type_span: Span,
+    /// `false` for primitive type names
+    pub is_struct: bool,
+    pub name: &'a str,
+    name_span: Span,
+    pub count: Option<usize>,
+    pub width: Option<(i64, Span)>,
+}
+
+impl Declaration<'_> {
+    /// The declaration with aliases resolved and single spaces between tokens
+    fn canonical(&self) -> String {
+        let mut out = String::new();
+        if let Some(variants) = &self.variants {
+            let variants = variants
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>();
+            out.push_str(&format!("enum {{{}}} ", variants.join(", ")));
         }
+        let type_name = match self.type_name {
+            "float" => "float32",
+            "double" => "float64",
+            type_name => type_name,
+        };
+        out.push_str(&format!("{} {}", type_name, self.name));
+        if let Some(count) = self.count {
+            out.push_str(&format!("[{}]", count));
+        }
+        if let Some((width, _)) = &self.width {
+            out.push_str(&format!(":{}", width));
+        }
+        out
     }
-    let tokens = tokens_collect.into_iter();
-    let mut cursor = offset;
-    tokens
-        .map(|token| token.unwrap())
-        .filter(|token| {
-            matches!(
-                token,
-                Token::Ident(_) | Token::Integer(_) | Token::TypeName(_) | Token::Semicolon
-            )
-        })
-        .collect::<Vec<_>>()
-        .split(|token| token == &Token::Semicolon)
-        .filter_map(|field_tokens| {
-            if field_tokens.len() < 2 || field_tokens.len() > 3 {
-                return None;
-            }
+}
 
-            let ident = match field_tokens[1] {
-                Token::Ident(ident) => ident,
-                _ => return None,
-            };
+fn schema_error(schema: &str, span: &Span, reason: SchemaErrorReason) -> SchemaError {
+    SchemaError {
+        position: span.start,
+        token: schema[span.clone()].to_owned(),
+        reason,
+    }
+}
 
-            match field_tokens[0] {
-                Token::Ident(sub_struct) => {
-                    if let Some(desc) = FrcStructDescDB::get(sub_struct) {
-                        let ret = parse_schema(desc.schema, format!("{}.", ident).as_str(), cursor);
-                        cursor += desc.size;
-                        return Some(ret);
+/// Splits a schema into its declarations, only checks the syntax
+pub(crate) fn parse_declarations(schema: &str) -> Result<Vec<Declaration<'_>>, SchemaError> {
+    let error = |span: &Span, reason| schema_error(schema, span, reason);
+    let mut tokens = Vec::new();
+    for (token, span) in Token::lexer(schema).spanned() {
+        match token {
+            Ok(token) => tokens.push((token, span)),
+            Err(_) => return Err(error(&span, SchemaErrorReason::InvalidToken)),
+        }
+    }
+    let end = schema.len()..schema.len();
+    let mut declarations = Vec::new();
+    for declaration in tokens.split(|(token, _)| token == &Token::Semicolon) {
+        if declaration.is_empty() {
+            continue;
+        }
+        let mut declaration = declaration.iter().peekable();
+
+        let mut variants = None;
+        if let Some((Token::EnumKeyword, _)) = declaration.peek() {
+            declaration.next();
+            match declaration.next() {
+                Some((Token::OpenBrace, _)) => {}
+                Some((_, span)) => return Err(error(span, SchemaErrorReason::Syntax)),
+                None => return Err(error(&end, SchemaErrorReason::Syntax)),
+            }
+            let mut enum_variants = Vec::new();
+            loop {
+                match declaration.next() {
+                    Some((Token::EnumVariant((name, value)), _)) => {
+                        enum_variants.push((*name, *value))
                     }
+                    Some((Token::Comma, _)) => {}
+                    Some((Token::CloseBrace, _)) => break,
+                    Some((_, span)) => return Err(error(span, SchemaErrorReason::Syntax)),
+                    None => return Err(error(&end, SchemaErrorReason::Syntax)),
                 }
-                Token::TypeName(type_name) => {
-                    let count = match field_tokens.get(2) {
-                        Some(Token::Integer(int)) => *int as usize,
-                        _ => 1,
-                    };
-                    if let Some(stype) = StructureFieldTypes::from_type(type_name, count) {
-                        let ret = vec![(format!("{}{}", prefix, ident), cursor, stype)];
-                        cursor += stype.size();
-                        return Some(ret);
-                    }
+            }
+            variants = Some(enum_variants);
+        }
+
+        let (type_name, type_span, is_struct) = match declaration.next() {
+            Some((Token::TypeName(type_name), span)) => (*type_name, span.clone(), false),
+            Some((Token::Ident(type_name), span)) => (*type_name, span.clone(), true),
+            Some((_, span)) => return Err(error(span, SchemaErrorReason::UnknownType)),
+            None => return Err(error(&end, SchemaErrorReason::Syntax)),
+        };
+        let (name, name_span) = match declaration.next() {
+            Some((Token::Ident(ident), span)) => (*ident, span.clone()),
+            Some((_, span)) => return Err(error(span, SchemaErrorReason::Syntax)),
+            None => return Err(error(&end, SchemaErrorReason::Syntax)),
+        };
+        let mut count = None;
+        let mut width = None;
+        match declaration.next() {
+            Some((Token::OpenBracket, _)) => {
+                count = match declaration.next() {
+                    Some((Token::Integer(int), span)) if *int > 0 => Some(
+                        usize::try_from(*int)
+                            .map_err(|_| error(span, SchemaErrorReason::TooLarge))?,
+                    ),
+                    Some((_, span)) => return Err(error(span, SchemaErrorReason::Syntax)),
+                    None => return Err(error(&end, SchemaErrorReason::Syntax)),
+                };
+                match declaration.next() {
+                    Some((Token::CloseBracket, _)) => {}
+                    Some((_, span)) => return Err(error(span, SchemaErrorReason::Syntax)),
+                    None => return Err(error(&end, SchemaErrorReason::Syntax)),
                 }
-                _ => {}
             }
-            None::<Vec<(String, usize, StructureFieldTypes)>>
+            Some((Token::Colon, _)) => match declaration.next() {
+                Some((Token::Integer(int), span)) => width = Some((*int, span.clone())),
+                Some((_, span)) => return Err(error(span, SchemaErrorReason::Syntax)),
+                None => return Err(error(&end, SchemaErrorReason::Syntax)),
+            },
+            Some((_, span)) => return Err(error(span, SchemaErrorReason::Syntax)),
+            None => {}
+        }
+        if let Some((_, span)) = declaration.next() {
+            return Err(error(span, SchemaErrorReason::Syntax));
+        }
+        declarations.push(Declaration {
+            variants,
+            type_name,
+            type_span,
+            is_struct,
+            name,
+            name_span,
+            count,
+            width,
+        });
+    }
+    Ok(declarations)
+}
+
+/// Re-emits a schema in its canonical form,
+/// `float`/`double` become `float32`/`float64` and declarations are separated by `"; "`
+/// without leading or trailing whitespace or semicolons
+///
+/// Nested structs are kept by name, so two schemas with the same canonical form
+/// have the same layout as long as their nested structs do
+pub fn normalize_schema(schema: &str) -> Result<String, SchemaError> {
+    Ok(parse_declarations(schema)?
+        .iter()
+        .map(Declaration::canonical)
+        .collect::<Vec<_>>()
+        .join("; "))
+}
+
+/// Parses a schema into its flattened fields starting at `offset`,
+/// errors in a nested struct are reported with positions in the nested schema
+#[cfg(test)]
+pub(crate) fn parse_schema_fields(
+    schema: &str,
+    prefix: &str,
+    offset: usize,
+) -> Result<Vec<SchemaField>, SchemaError> {
+    Ok(layout_schema(schema, prefix, offset, Some(&mut Vec::new()))?.0)
+}
+
+/// Lays out a schema starting at `offset`, returns its fields and the offset it ends at
+///
+/// Nested structs are expanded into their fields unless they're one of the `expanding` structs,
+/// which can't contain themselves again. Without `expanding` they only take up their registered size
+fn layout_schema(
+    schema: &str,
+    prefix: &str,
+    offset: usize,
+    mut expanding: Option<&mut Vec<String>>,
+) -> Result<(Vec<SchemaField>, usize), SchemaError> {
+    let error = |span: &Span, reason| schema_error(schema, span, reason);
+    let advance = |cursor: usize, size: Option<usize>, span: &Span| {
+        size.and_then(|size| cursor.checked_add(size))
+            .ok_or_else(|| error(span, SchemaErrorReason::TooLarge))
+    };
+    let mut fields = Vec::new();
+    let mut names = std::collections::HashSet::new();
+    let mut cursor = offset;
+    let mut unit: Option<BitfieldUnit> = None;
+    for declaration in parse_declarations(schema)? {
+        let name = format!("{}{}", prefix, declaration.name);
+        if !names.insert(declaration.name) {
+            return Err(error(
+                &declaration.name_span,
+                SchemaErrorReason::DuplicateField,
+            ));
+        }
+        let type_span = &declaration.type_span;
+        let count = declaration.count.unwrap_or(1);
+        let variants = declaration.variants.map(|variants| {
+            variants
+                .into_iter()
+                .map(|(name, value)| (name.to_owned(), value))
+                .collect::<Vec<_>>()
+        });
+        if declaration.width.is_none() {
+            unit = None;
+        }
+
+        if declaration.is_struct {
+            if declaration.width.is_some() {
+                return Err(error(type_span, SchemaErrorReason::InvalidBitfield));
+            }
+            let Some(expanding) = expanding.as_deref_mut() else {
+                let desc = FrcStructDescDB::get_owned(declaration.type_name)
+                    .ok_or_else(|| error(type_span, SchemaErrorReason::UnresolvedStruct))?;
+                cursor = advance(cursor, count.checked_mul(desc.size), type_span)?;
+                continue;
+            };
+            if expanding.iter().any(|name| name == declaration.type_name) {
+                return Err(error(type_span, SchemaErrorReason::RecursiveStruct));
+            }
+            let desc = FrcStructDescDB::get_owned(declaration.type_name)
+                .ok_or_else(|| error(type_span, SchemaErrorReason::UnresolvedStruct))?;
+            let end = advance(cursor, count.checked_mul(desc.size), type_span)?;
+            expanding.push(desc.type_str.to_string());
+            for i in 0..count {
+                let prefix = if count == 1 {
+                    format!("{}.", name)
+                } else {
+                    format!("{}[{}].", name, i)
+                };
+                let (nested, _) =
+                    layout_schema(&desc.schema, &prefix, cursor, Some(&mut *expanding))?;
+                fields.extend(nested);
+                // every element of a struct without a size has the same (lack of) fields
+                if desc.size == 0 {
+                    break;
+                }
+                cursor += desc.size;
+            }
+            expanding.pop();
+            cursor = end;
+            continue;
+        }
+        let Some((width, width_span)) = declaration.width else {
+            let stype = StructureFieldTypes::from_type(declaration.type_name, count)
+                .ok_or_else(|| error(type_span, SchemaErrorReason::UnknownType))?;
+            fields.push(SchemaField {
+                name,
+                offset: cursor,
+                stype,
+                // enums are only meaningful on integer fields
+                variants: variants.filter(|_| stype.is_integer()),
+                bits: None,
+            });
+            cursor = advance(
+                cursor,
+                stype.base_size().checked_mul(stype.count()),
+                type_span,
+            )?;
+            continue;
+        };
+        let stype = StructureFieldTypes::from_type(declaration.type_name, 1)
+            .ok_or_else(|| error(type_span, SchemaErrorReason::UnknownType))?;
+        let max_width = match stype {
+            StructureFieldTypes::Bool(_) => 1,
+            stype if stype.is_integer() => stype.size() as i64 * 8,
+            _ => return Err(error(type_span, SchemaErrorReason::InvalidBitfield)),
+        };
+        if width < 1 || width > max_width {
+            return Err(error(&width_span, SchemaErrorReason::InvalidBitfield));
+        }
+        let width = width as u32;
+        // bools join any unit they fit in, integers need a unit of their own size
+        let fits = unit.as_ref().is_some_and(|unit| {
+            (unit.storage == stype.size() || matches!(stype, StructureFieldTypes::Bool(_)))
+                && unit.used + width <= unit.storage as u32 * 8
+        });
+        if !fits {
+            unit = Some(BitfieldUnit {
+                offset: cursor,
+                storage: stype.size(),
+                used: 0,
+            });
+            cursor = advance(cursor, Some(stype.size()), type_span)?;
+        }
+        let current = unit.as_mut().expect("a bit-field unit was just started");
+        fields.push(SchemaField {
+            name,
+            offset: current.offset,
+            stype,
+            variants: variants.filter(|_| stype.is_integer()),
+            bits: Some(Bitfield {
+                storage: current.storage,
+                shift: current.used,
+                width,
+            }),
+        });
+        current.used += width;
+    }
+    Ok((fields, cursor))
+}
+
+/// Linearly interpolates every field of two packed instances of `desc`,
+/// returns `None` if the schema has any non numeric, enum or bit-fields or the buffers are the wrong size
+pub(crate) fn lerp_struct(
+    desc: &FrcStructDescHandle,
+    a: &Bytes,
+    b: &Bytes,
+    t: f64,
+) -> Option<Bytes> {
+    if a.len() != desc.size() || b.len() != desc.size() {
+        return None;
+    }
+    let fields = desc.fields().ok()?;
+    // values between two enum variants usually aren't a variant
+    if fields.is_empty()
+        || fields.iter().any(|field| {
+            !field.stype.is_numeric() || field.bits.is_some() || field.variants.is_some()
         })
-        .flatten()
-        .collect()
+    {
+        return None;
+    }
+    let mut out = BytesMut::from(&a[..]);
+    let mut element = BytesMut::with_capacity(8);
+    for SchemaField { offset, stype, .. } in fields {
+        let base_size = stype.base_size();
+        for i in 0..stype.count() {
+            let start = offset + i * base_size;
+            let end = start + base_size;
+            if end > desc.size() {
+                return None;
+            }
+            element.clear();
+            stype.lerp_element(&a[start..end], &b[start..end], t, &mut element);
+            out[start..end].copy_from_slice(&element);
+        }
+    }
+    Some(out.freeze())
+}
+
+/// Splits `val[2]` into `("val", Some(2))`
+fn split_index(path: &str) -> Option<(&str, Option<usize>)> {
+    match path.strip_suffix(']') {
+        Some(rest) => {
+            let (name, index) = rest.rsplit_once('[')?;
+            Some((name, Some(index.trim().parse().ok()?)))
+        }
+        None => Some((path, None)),
+    }
 }
 
+/// A struct whose layout is only known at runtime through its [`FrcStructDesc`]
+///
+/// Fields are addressed by their path in the schema,
+/// nested structs are `.` separated (`trans.x`) and array elements are indexed (`val[2]`)
 pub struct DynamicStructure {
-    desc: &'static FrcStructDesc,
+    desc: FrcStructDescHandle,
     buffer: BytesMut,
-    _map: HashMap<String, (usize, StructureFieldTypes), fxhash::FxBuildHasher>,
+    /// field path to index in `fields`
+    map: HashMap<String, usize, fxhash::FxBuildHasher>,
+    /// fields in schema order
+    fields: Vec<SchemaField>,
 }
 
 impl DynamicStructure {
-    pub fn try_new(desc: &'static FrcStructDesc, buffer: BytesMut) -> Result<Self, String> {
-        if buffer.len() != desc.size {
-            return Err(format!(
-                "Buffer size ({}) does not match structure size ({})",
-                buffer.len(),
-                desc.size
-            ));
-        }
-        let mut map = HashMap::with_hasher(fxhash::FxBuildHasher::default());
-        for field in parse_schema_toplevel(desc.schema) {
-            map.insert(field.0, (field.1, field.2));
+    /// Fails if the buffer isn't `desc.size` long or the schema is invalid (see [`FrcStructDesc::validate`])
+    pub fn try_new(
+        desc: impl Into<FrcStructDescHandle>,
+        buffer: BytesMut,
+    ) -> Result<Self, FrcValueError> {
+        let desc = desc.into();
+        if buffer.len() != desc.size() {
+            return Err(FrcValueError::SizeMismatch {
+                expected: desc.size(),
+                actual: buffer.len(),
+            });
         }
+        let fields = desc.fields()?;
+        let map = fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| (field.name.clone(), i))
+            .collect();
         Ok(DynamicStructure {
             desc,
             buffer,
-            _map: map,
+            map,
+            fields,
         })
     }
 
-    pub fn description(&self) -> &'static FrcStructDesc {
-        self.desc
+    pub fn description(&self) -> &FrcStructDescHandle {
+        &self.desc
     }
 
     pub fn update(&mut self, new: Box<Bytes>) {
         debug_assert!(new.len() == self.buffer.len());
         self.buffer[..].copy_from_slice(&new[..]);
     }
+
+    /// Resolves a path to the index of the field and the offset of the field or array element it points to,
+    /// the bool is true if the path points to a single array element
+    fn locate(&self, path: &str) -> Option<(usize, usize, bool)> {
+        let (name, index) = split_index(path)?;
+        let i = *self.map.get(name)?;
+        let field = &self.fields[i];
+        match index {
+            Some(index) if index < field.stype.count() => {
+                Some((i, field.offset + index * field.stype.base_size(), true))
+            }
+            Some(_) => None,
+            None => Some((i, field.offset, false)),
+        }
+    }
+
+    /// Reads a field, `None` if the path doesn't exist
+    ///
+    /// Integer fields are read as `Int` (`uint64` values above `i64::MAX` as `Double`),
+    /// `float32` as `Float` and `float64` as `Double`,
+    /// arrays as their array variant and char arrays as a `String`
+    pub fn get(&self, path: &str) -> Option<FrcValue> {
+        let (i, offset, element) = self.locate(path)?;
+        Some(self.fields[i].read(&self.buffer[offset..], element))
+    }
+
+    /// Writes a field, the value is converted to the field type if that can be done exactly
+    /// (floats may lose precision when written to a `float32` field)
+    ///
+    /// Enum fields also accept the variant name as a `String`
+    pub fn set(&mut self, path: &str, value: FrcValue) -> Result<(), FrcValueError> {
+        let (i, offset, element) = self
+            .locate(path)
+            .ok_or_else(|| FrcValueError::UnknownField(path.to_owned()))?;
+        let field = &self.fields[i];
+        let stype = field.stype;
+        let value = match (&field.variants, &value) {
+            (Some(variants), FrcValue::String(name)) if element || stype.count() == 1 => variants
+                .iter()
+                .find(|(variant, _)| variant == name)
+                .map(|(_, v)| FrcValue::Int(*v))
+                .ok_or(FrcValueError::InvalidCast(
+                    FrcType::String,
+                    stype.type_name(),
+                    CastErrorReason::Type,
+                ))?,
+            _ => value,
+        };
+        field
+            .write(&value, &mut self.buffer[offset..], element)
+            .map_err(|reason| {
+                FrcValueError::InvalidCast(value.get_type(), stype.type_name(), reason)
+            })
+    }
+
+    /// The variants of an enum field, `None` if the field isn't an enum
+    pub fn variants(&self, path: &str) -> Option<&[(String, i64)]> {
+        let (name, _) = split_index(path)?;
+        self.fields[*self.map.get(name)?].variants.as_deref()
+    }
+
+    /// The name of the current variant of an enum field (or element of an enum array),
+    /// `None` if the field isn't an enum or the value doesn't match any variant
+    pub fn variant(&self, path: &str) -> Option<&str> {
+        let (i, offset, element) = self.locate(path)?;
+        let field = &self.fields[i];
+        if !element && field.stype.count() != 1 {
+            return None;
+        }
+        let value = field.read(&self.buffer[offset..], true).as_i64()?;
+        field
+            .variants
+            .as_ref()?
+            .iter()
+            .find(|(_, v)| *v == value)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Every field in schema order, nested struct fields are flattened
+    pub fn iter(&self) -> impl Iterator<Item = (&str, FrcValue)> + '_ {
+        self.fields.iter().map(|field| {
+            (
+                field.name.as_str(),
+                field.read(&self.buffer[field.offset..], false),
+            )
+        })
+    }
+
+    /// A copy of the current contents as a struct value
+    pub fn to_value(&self) -> FrcValue {
+        FrcValue::Struct(
+            self.desc.clone(),
+            Box::new(Bytes::copy_from_slice(&self.buffer)),
+        )
+    }
+}
+
+#[cfg(feature = "json-casting")]
+pub(crate) mod json {
+    use serde_json::{Map, Value as JSONValue};
+
+    use bytes::{Buf, BufMut};
+
+    use super::{
+        split_index, DynamicStructure, FrcStructDescHandle, SchemaField, StructureFieldTypes,
+    };
+    use crate::{error::CastErrorReason, FrcType, FrcValue, FrcValueError};
+
+    /// Like `From<FrcValue> for JSONValue` but non finite floats become `null` instead of panicking
+    fn json_leaf(value: FrcValue) -> JSONValue {
+        let number =
+            |f: f64| serde_json::Number::from_f64(f).map_or(JSONValue::Null, JSONValue::Number);
+        match value {
+            FrcValue::Float(f) => number(f as f64),
+            FrcValue::Double(f) => number(f),
+            FrcValue::FloatArray(v) => v.into_iter().map(|f| number(f as f64)).collect(),
+            FrcValue::DoubleArray(v) => v.into_iter().map(number).collect(),
+            value => value.into(),
+        }
+    }
+
+    /// A `uint64` field as json integers, which (unlike the `Int` it's read as)
+    /// can hold values above `i64::MAX`
+    fn u64_to_json(field: &SchemaField, buffer: &[u8]) -> Option<JSONValue> {
+        if !matches!(field.stype, StructureFieldTypes::UInt64(_)) || field.bits.is_some() {
+            return None;
+        }
+        let count = field.stype.count();
+        let mut values = buffer
+            .chunks_exact(8)
+            .take(count)
+            .map(|mut e| JSONValue::from(e.get_u64_le()));
+        if count == 1 {
+            values.next()
+        } else {
+            Some(values.collect())
+        }
+    }
+
+    /// Writes json integers to a `uint64` field, `None` if they have to go through [`FrcValue`]
+    fn u64_from_json(field: &SchemaField, json: &JSONValue, buffer: &mut [u8]) -> Option<()> {
+        if !matches!(field.stype, StructureFieldTypes::UInt64(_)) || field.bits.is_some() {
+            return None;
+        }
+        let count = field.stype.count();
+        let values = match json {
+            JSONValue::Array(elements) if count != 1 => elements
+                .iter()
+                .map(JSONValue::as_u64)
+                .collect::<Option<Vec<_>>>()?,
+            json if count == 1 => vec![json.as_u64()?],
+            _ => return None,
+        };
+        if values.len() != count {
+            return None;
+        }
+        for (value, mut out) in values.into_iter().zip(buffer.chunks_exact_mut(8)) {
+            out.put_u64_le(value);
+        }
+        Some(())
+    }
+
+    /// Inserts a value at a flattened field path (`trans.x`, `modules[1].angle`),
+    /// nested structs become objects and arrays of nested structs arrays of objects
+    fn insert(object: &mut Map<String, JSONValue>, path: &str, value: JSONValue) {
+        let Some((head, rest)) = path.split_once('.') else {
+            object.insert(path.to_owned(), value);
+            return;
+        };
+        let nested = match split_index(head) {
+            Some((name, Some(index))) => {
+                let JSONValue::Array(elements) = object
+                    .entry(name)
+                    .or_insert_with(|| JSONValue::Array(Vec::new()))
+                else {
+                    return;
+                };
+                if elements.len() <= index {
+                    elements.resize_with(index + 1, || JSONValue::Object(Map::new()));
+                }
+                &mut elements[index]
+            }
+            _ => object
+                .entry(head)
+                .or_insert_with(|| JSONValue::Object(Map::new())),
+        };
+        if let JSONValue::Object(nested) = nested {
+            insert(nested, rest, value);
+        }
+    }
+
+    /// The value at a flattened field path
+    fn lookup<'a>(object: &'a Map<String, JSONValue>, path: &str) -> Option<&'a JSONValue> {
+        let Some((head, rest)) = path.split_once('.') else {
+            return object.get(path);
+        };
+        let nested = match split_index(head)? {
+            (name, Some(index)) => object.get(name)?.as_array()?.get(index)?,
+            (name, None) => object.get(name)?,
+        };
+        lookup(nested.as_object()?, rest)
+    }
+
+    /// Every leaf path of an object in the flattened field path format
+    fn leaf_paths(object: &Map<String, JSONValue>, prefix: &str, out: &mut Vec<String>) {
+        for (key, value) in object {
+            match value {
+                JSONValue::Object(nested) => {
+                    leaf_paths(nested, &format!("{}{}.", prefix, key), out)
+                }
+                JSONValue::Array(elements)
+                    if !elements.is_empty() && elements.iter().all(JSONValue::is_object) =>
+                {
+                    for (i, element) in elements.iter().enumerate() {
+                        if let JSONValue::Object(nested) = element {
+                            leaf_paths(nested, &format!("{}{}[{}].", prefix, key, i), out);
+                        }
+                    }
+                }
+                _ => out.push(format!("{}{}", prefix, key)),
+            }
+        }
+    }
+
+    impl DynamicStructure {
+        /// The fields as a json object, nested structs are nested objects
+        /// and enum fields with a known variant are the variant name
+        pub fn to_json(&self) -> JSONValue {
+            let mut object = Map::new();
+            for (field, (path, value)) in self.fields.iter().zip(self.iter()) {
+                let value = match self.variant(path) {
+                    Some(variant) => JSONValue::String(variant.to_owned()),
+                    None => u64_to_json(field, &self.buffer[field.offset..])
+                        .unwrap_or_else(|| json_leaf(value)),
+                };
+                insert(&mut object, path, value);
+            }
+            JSONValue::Object(object)
+        }
+    }
+
+    /// A packed struct as a json object
+    pub(crate) fn struct_json(
+        desc: &FrcStructDescHandle,
+        bytes: &[u8],
+    ) -> Result<JSONValue, FrcValueError> {
+        Ok(DynamicStructure::try_new(desc.clone(), bytes::BytesMut::from(bytes))?.to_json())
+    }
+
+    impl FrcValue {
+        /// Converts a `Struct` value into a json object using its schema,
+        /// see [`DynamicStructure::to_json`]
+        pub fn struct_to_json(&self) -> Result<JSONValue, FrcValueError> {
+            match self {
+                FrcValue::Struct(desc, bytes) => struct_json(desc, bytes),
+                _ => Err(FrcValueError::InvalidCast(
+                    self.get_type(),
+                    "Struct",
+                    CastErrorReason::Type,
+                )),
+            }
+        }
+
+        /// Packs a json object shaped like the output of [`FrcValue::struct_to_json`] into a `Struct` value
+        ///
+        /// Every field has to be present and fit its type, unknown fields are rejected
+        pub fn struct_from_json(
+            desc: impl Into<FrcStructDescHandle>,
+            json: &JSONValue,
+        ) -> Result<FrcValue, FrcValueError> {
+            let desc = desc.into();
+            let object = json.as_object().ok_or(FrcValueError::InvalidCast(
+                FrcType::Void,
+                desc.error_name(),
+                CastErrorReason::Type,
+            ))?;
+            let size = desc.size();
+            let mut structure = DynamicStructure::try_new(desc, bytes::BytesMut::zeroed(size))?;
+            for i in 0..structure.fields.len() {
+                let field = &structure.fields[i];
+                let path = field.name.clone();
+                let Some(json) = lookup(object, &path) else {
+                    return Err(FrcValueError::MissingField(path));
+                };
+                if u64_from_json(field, json, &mut structure.buffer[field.offset..]).is_some() {
+                    continue;
+                }
+                let value = match json {
+                    // non finite floats are written as null
+                    JSONValue::Null => FrcValue::Double(f64::NAN),
+                    json => FrcValue::try_from(json.clone())?,
+                };
+                structure.set(&path, value)?;
+            }
+            let mut leaves = Vec::new();
+            leaf_paths(object, "", &mut leaves);
+            if let Some(unknown) = leaves
+                .into_iter()
+                .find(|path| !structure.map.contains_key(path))
+            {
+                return Err(FrcValueError::UnknownField(unknown));
+            }
+            Ok(structure.to_value())
+        }
+    }
 }
@@ -0,0 +1,315 @@
+use std::{collections::HashMap, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{FrcTimeline, FrcTimelineLookup, FrcTimestamp, FrcTimestampedValue};
+
+/// Strips leading, trailing and repeated `/` so `"/Drive//Speed/"` and `"Drive/Speed"` are the same key
+pub fn normalize_path(path: &str) -> String {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn deserialize_normalized<'a, D, V>(deserializer: D) -> Result<HashMap<String, V>, D::Error>
+where
+    D: serde::Deserializer<'a>,
+    V: Deserialize<'a>,
+{
+    let values = HashMap::<String, V>::deserialize(deserializer)?;
+    let mut normalized = HashMap::with_capacity(values.len());
+    for (k, v) in values {
+        let path = normalize_path(&k);
+        // `"a/"` and `"a"` are the same key, neither can be picked over the other
+        if normalized.contains_key(&path) {
+            return Err(serde::de::Error::custom(format!(
+                "duplicate key {} after normalizing {}",
+                path, k
+            )));
+        }
+        normalized.insert(path, v);
+    }
+    Ok(normalized)
+}
+
+/// A borrowed view of every entry of a table under a `/` separated prefix
+///
+/// Keys returned from the view are relative to the prefix
+#[derive(Debug, Clone)]
+pub struct FrcSubTable<'a, V> {
+    values: &'a HashMap<String, V>,
+    prefix: String,
+}
+
+impl<'a, V> FrcSubTable<'a, V> {
+    fn new(values: &'a HashMap<String, V>, path: &str) -> Self {
+        let mut prefix = normalize_path(path);
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        Self { values, prefix }
+    }
+
+    /// The normalized path of this sub table, without a trailing `/`
+    pub fn path(&self) -> &str {
+        self.prefix.trim_end_matches('/')
+    }
+
+    pub fn get(&self, key: &str) -> Option<&'a V> {
+        self.values
+            .get(&format!("{}{}", self.prefix, normalize_path(key)))
+    }
+
+    pub fn sub(&self, path: &str) -> FrcSubTable<'a, V> {
+        FrcSubTable::new(
+            self.values,
+            &format!("{}{}", self.prefix, normalize_path(path)),
+        )
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a V)> + '_ {
+        self.values.iter().filter_map(|(k, v)| {
+            k.strip_prefix(self.prefix.as_str())
+                .filter(|k| !k.is_empty())
+                .map(|k| (k, v))
+        })
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// The names of the direct children of this sub table, both values and sub tables
+    pub fn children(&self) -> Vec<&'a str> {
+        let mut children = self
+            .keys()
+            .map(|k| k.split('/').next().unwrap_or(k))
+            .collect::<Vec<_>>();
+        children.sort_unstable();
+        children.dedup();
+        children
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+}
+
+/// The set of changes that turn one [`FrcTableInstant`] into another
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FrcTableDiff {
+    /// Keys that only exist in the newer instant
+    pub added: HashMap<String, FrcTimestampedValue>,
+    /// Keys whose value or timestamp changed, holding the newer value
+    pub updated: HashMap<String, FrcTimestampedValue>,
+    /// Keys that only exist in the older instant
+    pub removed: Vec<String>,
+}
+
+impl FrcTableDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+    pub fn len(&self) -> usize {
+        self.added.len() + self.updated.len() + self.removed.len()
+    }
+    /// Applies the changes to `instant`, turning the older instant of the diff into the newer one
+    pub fn apply(&self, instant: &mut FrcTableInstant) {
+        for key in &self.removed {
+            instant.values.remove(key);
+        }
+        for (k, v) in self.added.iter().chain(self.updated.iter()) {
+            instant.values.insert(k.clone(), v.clone());
+        }
+    }
+}
+
+/// The latest value of every key in a table
+///
+/// Keys are `/` separated paths and are stored normalized, see [`normalize_path`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FrcTableInstant {
+    #[serde(deserialize_with = "deserialize_normalized")]
+    values: HashMap<String, FrcTimestampedValue>, //just now
+}
+impl Display for FrcTableInstant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{")?;
+        for (i, (k, v)) in self.values.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: {}", k, v)?;
+        }
+        write!(f, "}}")
+    }
+}
+impl FrcTableInstant {
+    pub fn new() -> Self {
+        Self {
+            values: HashMap::new(),
+        }
+    }
+    /// If a key is repeated the first value for it is kept
+    pub fn from_tuples(mut tuples: Vec<(impl ToString, FrcTimestampedValue)>) -> Self {
+        let mut values = HashMap::new();
+        tuples.reverse();
+        for (k, v) in tuples {
+            values.insert(normalize_path(&k.to_string()), v);
+        }
+        Self { values }
+    }
+    pub fn set_field(&mut self, name: impl ToString, value: FrcTimestampedValue) {
+        self.values.insert(normalize_path(&name.to_string()), value);
+    }
+    pub fn remove_field(&mut self, name: &str) -> Option<FrcTimestampedValue> {
+        self.values.remove(&normalize_path(name))
+    }
+    pub fn get(&self, name: &str) -> Option<&FrcTimestampedValue> {
+        self.values.get(&normalize_path(name))
+    }
+    pub fn values(&self) -> &HashMap<String, FrcTimestampedValue> {
+        &self.values
+    }
+    pub fn into_values(self) -> HashMap<String, FrcTimestampedValue> {
+        self.values
+    }
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    /// A view of every key under `path`, `table.sub("Drive").get("Speed")` reads `Drive/Speed`
+    pub fn sub(&self, path: &str) -> FrcSubTable<'_, FrcTimestampedValue> {
+        FrcSubTable::new(&self.values, path)
+    }
+    /// Returns the changes needed to turn `self` into `newer`
+    pub fn diff(&self, newer: &FrcTableInstant) -> FrcTableDiff {
+        let mut diff = FrcTableDiff::default();
+        for (k, v) in &newer.values {
+            match self.values.get(k) {
+                None => {
+                    diff.added.insert(k.clone(), v.clone());
+                }
+                Some(old) if old != v => {
+                    diff.updated.insert(k.clone(), v.clone());
+                }
+                Some(_) => {}
+            }
+        }
+        diff.removed = self
+            .values
+            .keys()
+            .filter(|k| !newer.values.contains_key(*k))
+            .cloned()
+            .collect();
+        diff
+    }
+}
+
+/// Every value that has occured for every key in a table
+///
+/// Keys are `/` separated paths and are stored normalized, see [`normalize_path`]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(transparent)]
+pub struct FrcTableHistory {
+    #[serde(deserialize_with = "deserialize_normalized")]
+    values: HashMap<String, FrcTimeline>, //all values that have occured
+}
+impl FrcTableHistory {
+    pub fn new() -> Self {
+        Self {
+            values: HashMap::new(),
+        }
+    }
+    /// Inserts the value into the timeline of `name`, creating the timeline if needed
+    pub fn insert(&mut self, name: &str, value: FrcTimestampedValue) {
+        self.values
+            .entry(normalize_path(name))
+            .or_default()
+            .insert(value);
+    }
+    /// Records every value of the instant into the history
+    pub fn record(&mut self, instant: &FrcTableInstant) {
+        for (k, v) in &instant.values {
+            self.values.entry(k.clone()).or_default().insert(v.clone());
+        }
+    }
+    pub fn get(&self, name: &str) -> Option<&FrcTimeline> {
+        self.values.get(&normalize_path(name))
+    }
+    pub fn values(&self) -> &HashMap<String, FrcTimeline> {
+        &self.values
+    }
+    pub fn into_values(self) -> HashMap<String, FrcTimeline> {
+        self.values
+    }
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    /// A view of every key under `path`, `table.sub("Drive").get("Speed")` reads `Drive/Speed`
+    pub fn sub(&self, path: &str) -> FrcSubTable<'_, FrcTimeline> {
+        FrcSubTable::new(&self.values, path)
+    }
+    /// The table as it was at `timestamp`, keys with no value at or before `timestamp` are left out
+    pub fn instant_at(&self, timestamp: FrcTimestamp) -> FrcTableInstant {
+        let values = self
+            .values
+            .iter()
+            .filter_map(|(k, timeline)| {
+                timeline
+                    .get_by_timestamp(timestamp, FrcTimelineLookup::Before)
+                    .map(|v| (k.clone(), v.clone()))
+            })
+            .collect();
+        FrcTableInstant { values }
+    }
+    /// The latest value of every key
+    pub fn latest(&self) -> FrcTableInstant {
+        self.instant_at(FrcTimestamp::MAX)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "table", rename_all = "lowercase")]
+pub enum FrcTable {
+    Instant(FrcTableInstant),
+    History(FrcTableHistory),
+}
+impl FrcTable {
+    /// The table as it was at `timestamp`, for an instant table any values newer than `timestamp` are left out
+    pub fn instant_at(&self, timestamp: FrcTimestamp) -> FrcTableInstant {
+        match self {
+            FrcTable::Instant(instant) => FrcTableInstant {
+                values: instant
+                    .values
+                    .iter()
+                    .filter(|(_, v)| v.timestamp <= timestamp)
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+            },
+            FrcTable::History(history) => history.instant_at(timestamp),
+        }
+    }
+}
+
+impl From<FrcTableInstant> for FrcTable {
+    fn from(instant: FrcTableInstant) -> Self {
+        FrcTable::Instant(instant)
+    }
+}
+impl From<FrcTableHistory> for FrcTable {
+    fn from(history: FrcTableHistory) -> Self {
+        FrcTable::History(history)
+    }
+}
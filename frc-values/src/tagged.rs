@@ -0,0 +1,301 @@
+use std::fmt;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bytes::Bytes;
+use serde::{
+    de::{DeserializeSeed, MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Serialize,
+};
+
+use crate::{
+    error::CastErrorReason, structure::FrcStructDescDB, FrcTaggedValue, FrcType, FrcTypeString,
+    FrcValue, FrcValueError,
+};
+
+/// The type string of `Void` values in the tagged form
+const VOID: &str = "void";
+
+/// Deserializes the payload of a tagged value whose type string is already known
+///
+/// Byte payloads can be raw bytes, a byte array or (in human readable formats) a base64 string,
+/// struct descriptions are resolved through [`FrcStructDescDB::get_handle`]
+/// (and protobuf descriptions through `FrcProtoDescDB::get`),
+/// `None` is the type of `Void` values
+#[derive(Debug, Clone, Copy)]
+pub struct FrcValueSeed<'a> {
+    type_string: Option<&'a FrcTypeString>,
+}
+
+impl<'a> FrcValueSeed<'a> {
+    pub fn new(type_string: Option<&'a FrcTypeString>) -> Self {
+        Self { type_string }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for FrcValueSeed<'_> {
+    type Value = FrcValue;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let Some(type_string) = self.type_string else {
+            return <()>::deserialize(deserializer).map(|_| FrcValue::Void);
+        };
+        let value = match type_string.frc_type() {
+            FrcType::Void => FrcValue::Void,
+            FrcType::Boolean => FrcValue::Boolean(bool::deserialize(deserializer)?),
+            FrcType::Int => FrcValue::Int(i64::deserialize(deserializer)?),
+            FrcType::Double => FrcValue::Double(f64::deserialize(deserializer)?),
+            FrcType::Float => FrcValue::Float(f32::deserialize(deserializer)?),
+            FrcType::String => FrcValue::String(String::deserialize(deserializer)?),
+            FrcType::BoolArray => FrcValue::BooleanArray(Vec::deserialize(deserializer)?),
+            FrcType::IntArray => FrcValue::IntArray(Vec::deserialize(deserializer)?),
+            FrcType::FloatArray => FrcValue::FloatArray(Vec::deserialize(deserializer)?),
+            FrcType::DoubleArray => FrcValue::DoubleArray(Vec::deserialize(deserializer)?),
+            FrcType::StringArray => FrcValue::StringArray(Vec::deserialize(deserializer)?),
+            _ => {
+                let bytes = if deserializer.is_human_readable() {
+                    deserializer.deserialize_any(BytesVisitor)?
+                } else {
+                    deserializer.deserialize_bytes(BytesVisitor)?
+                };
+                FrcValue::Raw(Box::new(bytes))
+            }
+        };
+        FrcValue::from_untagged(Some(type_string), value).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Accepts a base64 string, raw bytes or a sequence of bytes
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Bytes;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("bytes, a byte array or a base64 string")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        STANDARD.decode(v).map(Bytes::from).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(Bytes::copy_from_slice(v))
+    }
+
+    fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(Bytes::from(v))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(byte) = seq.next_element::<u8>()? {
+            bytes.push(byte);
+        }
+        Ok(Bytes::from(bytes))
+    }
+}
+
+fn payload_bytes(value: FrcValue, r#type: FrcType) -> Result<Bytes, FrcValueError> {
+    let invalid = |value: &FrcValue| {
+        FrcValueError::InvalidCast(value.get_type(), r#type.name(), CastErrorReason::Type)
+    };
+    match value {
+        FrcValue::Raw(bytes) => Ok(*bytes),
+        FrcValue::String(s) => STANDARD
+            .decode(&s)
+            .map(Bytes::from)
+            .map_err(|_| invalid(&FrcValue::String(s))),
+        FrcValue::IntArray(ints) => ints
+            .iter()
+            .map(|i| u8::try_from(*i).ok())
+            .collect::<Option<Vec<u8>>>()
+            .map(Bytes::from)
+            .ok_or_else(|| invalid(&FrcValue::IntArray(ints))),
+        value if value.is_array() && value.is_empty() => Ok(Bytes::new()),
+        value => Err(invalid(&value)),
+    }
+}
+
+impl FrcValue {
+    /// Reinterprets a value deserialized without its type (like the untagged `FrcValue` form)
+    /// as a value of `type_string`, `None` being the type of `Void`
+    ///
+    /// Numbers are converted to the requested width, byte payloads can be
+    /// raw bytes, a byte array or a base64 string
+    /// and struct descriptions are looked up in [`FrcStructDescDB`] (owned descriptions first)
+    pub fn from_untagged(
+        type_string: Option<&FrcTypeString>,
+        value: FrcValue,
+    ) -> Result<FrcValue, FrcValueError> {
+        let Some(type_string) = type_string else {
+            return match value {
+                FrcValue::Void => Ok(FrcValue::Void),
+                value => Err(FrcValueError::InvalidCast(
+                    value.get_type(),
+                    FrcType::Void.name(),
+                    CastErrorReason::Type,
+                )),
+            };
+        };
+        let r#type = type_string.frc_type();
+        match r#type {
+            FrcType::Struct => {
+                let name = type_string.type_name().unwrap_or_default();
+                let desc = FrcStructDescDB::get_handle(name)
+                    .ok_or_else(|| FrcValueError::UnknownType(type_string.to_string()))?;
+                let bytes = payload_bytes(value, r#type)?;
+                if bytes.len() != desc.size() {
+                    return Err(FrcValueError::InvalidCast(
+                        FrcType::Raw,
+                        desc.error_name(),
+                        CastErrorReason::Type,
+                    ));
+                }
+                Ok(FrcValue::Struct(desc, Box::new(bytes)))
+            }
+            FrcType::StructArray => {
+                let name = type_string.type_name().unwrap_or_default();
+                let desc = FrcStructDescDB::get_handle(name)
+                    .ok_or_else(|| FrcValueError::UnknownType(type_string.to_string()))?;
+                FrcValue::try_struct_array(desc, payload_bytes(value, r#type)?)
+            }
+            #[cfg(feature = "protobuf")]
+            FrcType::Protobuf => {
+                let name = type_string.type_name().unwrap_or_default();
+                let desc = crate::proto::FrcProtoDescDB::get(name)
+                    .ok_or_else(|| FrcValueError::UnknownType(type_string.to_string()))?;
+                Ok(FrcValue::Protobuf(
+                    desc,
+                    Box::new(payload_bytes(value, r#type)?),
+                ))
+            }
+            FrcType::Raw => Ok(FrcValue::Raw(Box::new(payload_bytes(value, r#type)?))),
+            // untagged empty arrays always come back as the first array variant
+            _ if value.is_array() && value.is_empty() => FrcValue::default_value(r#type).ok_or(
+                FrcValueError::InvalidCast(value.get_type(), r#type.name(), CastErrorReason::Type),
+            ),
+            FrcType::Float | FrcType::FloatArray => value.coerce_to_lossy(r#type),
+            _ => value.coerce_to(r#type),
+        }
+    }
+}
+
+/// The payload of a tagged value, byte payloads are base64 in human readable formats
+struct Payload<'a>(&'a FrcValue);
+
+impl Serialize for Payload<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let bytes = match self.0 {
+            FrcValue::Raw(bytes) | FrcValue::Struct(_, bytes) | FrcValue::StructArray(_, bytes) => {
+                bytes
+            }
+            #[cfg(feature = "protobuf")]
+            FrcValue::Protobuf(_, bytes) => bytes,
+            value => return value.serialize(serializer),
+        };
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&STANDARD.encode(&bytes[..]))
+        } else {
+            serializer.serialize_bytes(&bytes[..])
+        }
+    }
+}
+
+/// Serialized as `{"type": <type string>, "value": <payload>}`,
+/// the type string keeps struct and protobuf names so every variant can be read back
+impl Serialize for FrcTaggedValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(2))?;
+        match self.value.type_string() {
+            Some(type_string) => map.serialize_entry("type", &type_string)?,
+            None => map.serialize_entry("type", VOID)?,
+        }
+        map.serialize_entry("value", &Payload(&self.value))?;
+        map.end()
+    }
+}
+
+fn parse_type(s: &str) -> Result<Option<FrcTypeString>, FrcValueError> {
+    match s {
+        VOID => Ok(None),
+        // older versions tagged values with the `FrcType` name
+        "bool[]" => Ok(Some(FrcTypeString::BooleanArray)),
+        // those names don't say which struct it was, so the payload can only be kept as bytes
+        "struct" | "struct[]" => Ok(Some(FrcTypeString::Raw)),
+        _ => s.parse().map(Some),
+    }
+}
+
+struct TaggedVisitor;
+
+impl<'de> Visitor<'de> for TaggedVisitor {
+    type Value = FrcTaggedValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map with a type string and a value")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        use serde::de::Error;
+
+        let mut type_string: Option<Option<FrcTypeString>> = None;
+        let mut value: Option<FrcValue> = None;
+        // the value can't be interpreted until the type is known,
+        // so a value before the type is read untagged and converted afterwards
+        let mut untagged: Option<FrcValue> = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "type" => {
+                    let s = map.next_value::<String>()?;
+                    type_string = Some(parse_type(&s).map_err(A::Error::custom)?);
+                }
+                "value" => match &type_string {
+                    Some(type_string) => {
+                        value = Some(map.next_value_seed(FrcValueSeed::new(type_string.as_ref()))?)
+                    }
+                    None => untagged = Some(map.next_value()?),
+                },
+                _ => {
+                    map.next_value::<serde::de::IgnoredAny>()?;
+                }
+            }
+        }
+        let type_string = type_string.ok_or_else(|| A::Error::missing_field("type"))?;
+        let value = match (value, untagged) {
+            (Some(value), _) => value,
+            (None, Some(untagged)) => {
+                FrcValue::from_untagged(type_string.as_ref(), untagged).map_err(A::Error::custom)?
+            }
+            (None, None) => return Err(A::Error::missing_field("value")),
+        };
+        Ok(FrcTaggedValue {
+            r#type: value.get_type(),
+            value,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for FrcTaggedValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(TaggedVisitor)
+    }
+}
@@ -1,11 +1,14 @@
 
-use bytes::{Buf, BufMut};
+use bytes::{Buf, BufMut, BytesMut};
 
 use crate::{
     structure::{self, FrcStructure, StructureFieldTypes},
     structure::{FrcStructDesc, FrcStructDescDB},
-    FrcValue,
+    CastErrorReason, FrcInterpolation, FrcTable, FrcTableHistory, FrcTableInstant, FrcTimeline, FrcTimelineLookup,
+    FrcTaggedValue, FrcTimestampedValue, FrcType, FrcTypeString, FrcValue, FrcValueBuffer, FrcValueError,
+    FrcValueRef, FrcValueSeed,
 };
+use serde::de::DeserializeSeed;
 
 #[test]
 fn test_value_serde() {
@@ -36,6 +39,389 @@ fn test_table_serde_simple() {
     assert_f64_eq(value.try_into().unwrap(), 1.0);
 }
 
+#[test]
+fn test_borrowing_accessors() {
+    let value = FrcValue::from(vec![1.0, 2.0]);
+    let slice: &[f64] = (&value).try_into().unwrap();
+    assert_eq!(value.as_f64_slice(), Some(slice));
+    assert_eq!(Vec::<f32>::try_from(&value).unwrap(), vec![1.0f32, 2.0]);
+    assert!(<&[i64]>::try_from(&value).is_err());
+    assert_eq!(value.as_bool(), None);
+
+    let value = FrcValue::from("text");
+    assert_eq!(<&str>::try_from(&value).unwrap(), "text");
+    assert_eq!(value.as_str(), Some("text"));
+    assert_eq!(String::try_from(&value).unwrap(), "text");
+
+    let value = FrcValue::from(300i64);
+    assert_eq!(i16::try_from(&value).unwrap(), 300);
+    assert!(u8::try_from(&value).is_err());
+    assert_eq!(value.as_i64(), Some(300));
+    let value = FrcValue::from(vec![1i64, -1]);
+    assert_eq!(Vec::<i32>::try_from(&value).unwrap(), vec![1, -1]);
+    assert!(Vec::<u32>::try_from(&value).is_err());
+    assert!(bool::try_from(&FrcValue::from(true)).unwrap());
+
+    let value = FrcValue::from_struct(Meter { value: 2.0 });
+    assert_eq!(value.as_bytes().map(|b| b.len()), Some(8));
+    assert_eq!(value.as_struct::<Meter>().unwrap(), Meter { value: 2.0 });
+    assert!(FrcValue::from(1.0).as_struct::<Meter>().is_err());
+}
+
+#[test]
+fn test_value_buffer() {
+    let values = vec![
+        FrcValue::from(vec!["a", "bc"]),
+        FrcValue::from(Vec::<String>::new()),
+        FrcValue::from(vec!["def"]),
+    ];
+    let buffer = FrcValueBuffer::try_from_values(FrcType::StringArray, &values).unwrap();
+    assert_eq!(buffer.len(), 3);
+    match buffer.get(0).unwrap() {
+        FrcValueRef::StringArray(strings) => {
+            assert_eq!(strings.iter().collect::<Vec<_>>(), vec!["a", "bc"])
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(buffer.to_values(), values);
+    assert!(buffer.get(3).is_none());
+
+    let mut buffer = FrcValueBuffer::new(FrcType::Raw);
+    buffer
+        .push(&FrcValue::Raw(Box::new(bytes::Bytes::from_static(b"xyz"))))
+        .unwrap();
+    assert_eq!(buffer.get(0), Some(FrcValueRef::Raw(b"xyz")));
+    assert!(buffer.push(&FrcValue::from(1.0)).is_err());
+
+    let mut buffer = FrcValueBuffer::with_capacity(FrcType::Struct, 2);
+    buffer.push(&FrcValue::from_struct(Meter { value: 1.0 })).unwrap();
+    buffer.push(&FrcValue::from_struct(Meter { value: 2.0 })).unwrap();
+    assert_eq!(buffer.struct_desc().unwrap().type_str(), "Meter");
+    assert_eq!(
+        buffer.get_value(1).unwrap().as_struct::<Meter>().unwrap(),
+        Meter { value: 2.0 }
+    );
+    buffer.clear();
+    assert!(buffer.is_empty());
+
+    // zero sized structs are still counted
+    let empty: &'static FrcStructDesc = Box::leak(Box::new(FrcStructDesc {
+        schema: "",
+        type_str: "BufferEmpty",
+        size: 0,
+    }));
+    let value = FrcValue::Struct(empty.into(), Box::new(bytes::Bytes::new()));
+    let mut buffer = FrcValueBuffer::new(FrcType::Struct);
+    buffer.push(&value).unwrap();
+    buffer.push(&value).unwrap();
+    assert_eq!(buffer.len(), 2);
+    assert_eq!(buffer.get_value(1), Some(value));
+    assert!(buffer.get(2).is_none());
+
+    let mut buffer = FrcValueBuffer::new(FrcType::StructArray);
+    let meters = |len| {
+        FrcValue::StructArray(
+            (&<Meter as FrcStructure>::DESCRIPTION).into(),
+            Box::new(bytes::Bytes::from(vec![0u8; len])),
+        )
+    };
+    assert!(buffer.push(&meters(12)).is_err());
+    buffer.push(&meters(16)).unwrap();
+    assert!(buffer.push(&meters(7)).is_err());
+    assert_eq!(buffer.len(), 1);
+
+    let doubles = FrcValueBuffer::try_from_values(
+        FrcType::DoubleArray,
+        &[FrcValue::from(vec![1.0, 2.0]), FrcValue::from(vec![3.0])],
+    )
+    .unwrap();
+    assert_eq!(doubles.get(1), Some(FrcValueRef::DoubleArray(&[3.0])));
+    assert!(doubles
+        .iter()
+        .all(|v| v.get_type() == FrcType::DoubleArray));
+}
+
+#[test]
+fn test_coercion() {
+    fn reason(result: Result<impl std::fmt::Debug, FrcValueError>) -> CastErrorReason {
+        match result.unwrap_err() {
+            FrcValueError::InvalidCast(_, _, reason) => reason,
+            err => panic!("unexpected error {}", err),
+        }
+    }
+
+    assert_eq!(FrcValue::Int(3).as_f64_exact().unwrap(), 3.0);
+    assert_eq!(FrcValue::Double(-4.0).as_i64_exact().unwrap(), -4);
+    assert!(matches!(
+        reason(FrcValue::Double(1.5).as_i64_exact()),
+        CastErrorReason::PrecisionLoss
+    ));
+    assert_eq!(FrcValue::Double(1.5).as_i64_lossy().unwrap(), 1);
+    assert!(matches!(
+        reason(FrcValue::Double(1e19).as_i64_lossy()),
+        CastErrorReason::Overflow
+    ));
+    assert!(matches!(
+        reason(FrcValue::Double(-1e19).as_i64_lossy()),
+        CastErrorReason::Underflow
+    ));
+    let big = (1i64 << 53) + 1;
+    assert!(matches!(
+        reason(FrcValue::Int(big).as_f64_exact()),
+        CastErrorReason::PrecisionLoss
+    ));
+    assert_eq!(FrcValue::Int(big).as_f64_lossy().unwrap(), big as f64);
+    assert!(matches!(
+        reason(FrcValue::Int(i64::MAX).coerce_to(FrcType::Double)),
+        CastErrorReason::PrecisionLoss
+    ));
+    assert!(matches!(
+        reason(FrcValue::from("1").as_f64_lossy()),
+        CastErrorReason::Type
+    ));
+
+    assert_eq!(
+        FrcValue::Double(0.5).coerce_to(FrcType::Float).unwrap(),
+        FrcValue::Float(0.5)
+    );
+    assert!(matches!(
+        reason(FrcValue::Double(0.1).coerce_to(FrcType::Float)),
+        CastErrorReason::PrecisionLoss
+    ));
+    assert!(matches!(
+        reason(FrcValue::Double(1e300).coerce_to_lossy(FrcType::Float)),
+        CastErrorReason::Overflow
+    ));
+    assert_eq!(
+        FrcValue::from(vec![1i64, 2]).coerce_to(FrcType::DoubleArray).unwrap(),
+        FrcValue::from(vec![1.0, 2.0])
+    );
+    assert!(matches!(
+        reason(FrcValue::from(vec![1.0, 2.5]).coerce_to(FrcType::IntArray)),
+        CastErrorReason::PrecisionLoss
+    ));
+    assert_eq!(
+        FrcValue::from(vec![1.0, 2.5]).coerce_to_lossy(FrcType::IntArray).unwrap(),
+        FrcValue::from(vec![1i64, 2])
+    );
+    assert!(matches!(
+        reason(FrcValue::from(true).coerce_to(FrcType::Int)),
+        CastErrorReason::Type
+    ));
+}
+
+#[test]
+fn test_timeline() {
+    let empty = FrcTimeline::new();
+    assert!(empty
+        .get_by_timestamp(10, FrcTimelineLookup::Nearest)
+        .is_none());
+    assert!(empty.range(..).is_empty());
+
+    let mut timeline = FrcTimeline::from_vec(vec![
+        FrcTimestampedValue::new(30, FrcValue::from(3.0)),
+        FrcTimestampedValue::new(10, FrcValue::from(1.0)),
+    ]);
+    assert_eq!(
+        timeline.insert(FrcTimestampedValue::new(20, FrcValue::from(2.0))),
+        1
+    );
+    assert_eq!(timeline.get_type(), Some(FrcType::Double));
+
+    let lookup = |ts, lookup| timeline.get_by_timestamp(ts, lookup).map(|v| v.timestamp);
+    assert_eq!(lookup(20, FrcTimelineLookup::Before), Some(20));
+    assert_eq!(lookup(24, FrcTimelineLookup::Before), Some(20));
+    assert_eq!(lookup(24, FrcTimelineLookup::After), Some(30));
+    assert_eq!(lookup(24, FrcTimelineLookup::Nearest), Some(20));
+    assert_eq!(lookup(26, FrcTimelineLookup::Nearest), Some(30));
+    assert_eq!(lookup(5, FrcTimelineLookup::Before), None);
+    assert_eq!(lookup(35, FrcTimelineLookup::After), None);
+    assert_eq!(lookup(35, FrcTimelineLookup::Nearest), Some(30));
+
+    assert_eq!(timeline.range(10..30).len(), 2);
+    assert_eq!(timeline.range(11..=30).len(), 2);
+    assert!(timeline.range(40..).is_empty());
+
+    timeline.merge(FrcTimeline::from_vec(vec![
+        FrcTimestampedValue::new(15, FrcValue::from(true)),
+        FrcTimestampedValue::new(40, FrcValue::from(4.0)),
+    ]));
+    assert_eq!(
+        timeline.iter().map(|v| v.timestamp).collect::<Vec<_>>(),
+        vec![10, 15, 20, 30, 40]
+    );
+    assert!(!timeline.is_all_same_type());
+    assert_eq!(timeline.get_type(), None);
+
+    let json = serde_json::to_string(&timeline).unwrap();
+    let reversed: Vec<FrcTimestampedValue> = timeline.clone().into_iter().rev().collect();
+    let de: FrcTimeline = serde_json::from_str(&serde_json::to_string(&reversed).unwrap()).unwrap();
+    assert_eq!(serde_json::to_string(&de).unwrap(), json);
+}
+
+#[test]
+fn test_tables() {
+    let mut history = FrcTableHistory::new();
+    history.insert("/Drive/Left/Speed", FrcValue::from(1.0).to_timestamped(10));
+    history.insert("Drive/Left/Speed", FrcValue::from(2.0).to_timestamped(20));
+    history.insert(
+        "Drive//Right/Speed/",
+        FrcValue::from(3.0).to_timestamped(15),
+    );
+    history.insert("Enabled", FrcValue::from(true).to_timestamped(5));
+
+    let early = history.instant_at(12);
+    assert_eq!(early.len(), 2);
+    assert_eq!(
+        early.get("Drive/Left/Speed").unwrap().value,
+        FrcValue::from(1.0)
+    );
+    let late = history.latest();
+    assert_eq!(late.len(), 3);
+
+    let drive = late.sub("Drive");
+    assert_eq!(drive.children(), vec!["Left", "Right"]);
+    assert_eq!(drive.sub("Left").get("Speed").unwrap().timestamp, 20);
+    assert_eq!(history.sub("/Drive/Right").len(), 1);
+    assert!(late.sub("Arm").is_empty());
+
+    let diff = early.diff(&late);
+    assert_eq!(
+        diff.added.keys().collect::<Vec<_>>(),
+        vec!["Drive/Right/Speed"]
+    );
+    assert_eq!(
+        diff.updated.keys().collect::<Vec<_>>(),
+        vec!["Drive/Left/Speed"]
+    );
+    assert!(diff.removed.is_empty());
+    let mut patched = early.clone();
+    diff.apply(&mut patched);
+    assert_eq!(patched, late);
+    assert!(late.diff(&patched).is_empty());
+
+    let table = FrcTable::from(late.clone());
+    let json = serde_json::to_string(&table).unwrap();
+    assert!(json.starts_with(r#"{"type":"instant","table":{"#));
+    assert_eq!(serde_json::from_str::<FrcTable>(&json).unwrap(), table);
+    let history_table = FrcTable::from(history);
+    let json = serde_json::to_string(&history_table).unwrap();
+    assert_eq!(
+        serde_json::from_str::<FrcTable>(&json).unwrap(),
+        history_table
+    );
+    assert_eq!(history_table.instant_at(12), early);
+    assert_eq!(table.instant_at(12).len(), 1);
+    assert_eq!(FrcTableInstant::new().diff(&early).added.len(), 2);
+
+    let value = serde_json::to_string(&FrcValue::from(1.0).to_timestamped(1)).unwrap();
+    let instant: FrcTableInstant = serde_json::from_str(&format!(r#"{{"/a/":{}}}"#, value)).unwrap();
+    assert!(instant.get("a").is_some());
+    let duplicate = format!(r#"{{"a":{},"a/":{}}}"#, value, value);
+    assert!(serde_json::from_str::<FrcTableInstant>(&duplicate).is_err());
+}
+
+#[test]
+fn test_interpolation() {
+    use crate as frc_values;
+
+    let timeline = FrcTimeline::from_vec(vec![
+        FrcValue::from(0.0).to_timestamped(10),
+        FrcValue::from(10.0).to_timestamped(20),
+    ]);
+    assert_eq!(timeline.sample(5, FrcInterpolation::Linear), None);
+    assert_eq!(
+        timeline.sample(15, FrcInterpolation::Hold),
+        Some(FrcValue::from(0.0))
+    );
+    assert_eq!(
+        timeline.sample(15, FrcInterpolation::Linear),
+        Some(FrcValue::from(5.0))
+    );
+    assert_eq!(
+        timeline.sample(50, FrcInterpolation::Linear),
+        Some(FrcValue::from(10.0))
+    );
+    let resampled = timeline.resample([0, 12, 20], FrcInterpolation::Linear);
+    assert_eq!(resampled.len(), 2);
+    assert_eq!(resampled.first().unwrap().value, FrcValue::from(2.0));
+
+    let a = FrcValue::from(1i64).to_timestamped(0);
+    let b = FrcValue::from(4i64).to_timestamped(3);
+    assert_eq!(a.interpolate(&b, 2, FrcInterpolation::Linear), FrcValue::Int(3));
+    let a = FrcValue::from(vec![0.0f32, 2.0]).to_timestamped(0);
+    let b = FrcValue::from(vec![1.0f32, 4.0]).to_timestamped(2);
+    assert_eq!(
+        a.interpolate(&b, 1, FrcInterpolation::Linear),
+        FrcValue::from(vec![0.5f32, 3.0])
+    );
+    let a = FrcValue::from("a").to_timestamped(0);
+    let b = FrcValue::from("b").to_timestamped(2);
+    assert_eq!(a.interpolate(&b, 1, FrcInterpolation::Linear), FrcValue::from("a"));
+    assert_eq!(a.interpolate(&b, 2, FrcInterpolation::Linear), FrcValue::from("b"));
+
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    struct LerpStruct {
+        position: Meter,
+        ticks: [u16; 2],
+    }
+    let a = FrcValue::from_struct(LerpStruct {
+        position: Meter { value: 1.0 },
+        ticks: [0, 100],
+    });
+    let b = FrcValue::from_struct(LerpStruct {
+        position: Meter { value: 2.0 },
+        ticks: [10, 50],
+    });
+    let mid: LerpStruct = a.lerp(&b, 0.5).unwrap().try_into_struct().unwrap();
+    assert_eq!(
+        mid,
+        LerpStruct {
+            position: Meter { value: 1.5 },
+            ticks: [5, 75],
+        }
+    );
+
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    struct FlagStruct {
+        flag: bool,
+        value: f64,
+    }
+    let a = FrcValue::from_struct(FlagStruct {
+        flag: false,
+        value: 0.0,
+    });
+    let b = FrcValue::from_struct(FlagStruct {
+        flag: true,
+        value: 1.0,
+    });
+    assert_eq!(a.lerp(&b, 0.5), None);
+
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    #[repr(u8)]
+    enum LerpMode {
+        Disabled,
+        Enabled,
+    }
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    struct ModeStruct {
+        mode: LerpMode,
+        value: f64,
+    }
+    let a = FrcValue::from_struct(ModeStruct {
+        mode: LerpMode::Disabled,
+        value: 0.0,
+    })
+    .to_timestamped(0);
+    let b = FrcValue::from_struct(ModeStruct {
+        mode: LerpMode::Enabled,
+        value: 1.0,
+    })
+    .to_timestamped(2);
+    assert_eq!(a.value.lerp(&b.value, 0.5), None);
+    // enum fields hold the previous value like any other value that can't be interpolated
+    assert_eq!(a.interpolate(&b, 1, FrcInterpolation::Linear), a.value);
+}
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 struct Meter {
@@ -89,11 +475,25 @@ fn test_structures() {
     let nested_struct2: NestedTestStruct = value.try_into_struct().unwrap();
     assert_eq!(nested_struct, nested_struct2);
 
+    // same size and layout as `Meter`, but a different struct
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    struct Feet {
+        value: f64,
+    }
+
+    let value = FrcValue::from_struct(test_struct);
+    assert!(value.as_struct::<Feet>().is_err());
+    assert!(value.clone().try_into_struct::<Feet>().is_err());
+    let value = FrcValue::from_struct_slice(&[test_struct]);
+    assert!(value.as_struct_vec::<Feet>().is_err());
+    assert!(value.try_into_struct_vec::<Feet>().is_err());
+
     FrcStructDescDB::add(FrcStructDesc {
         schema: "bool idk;",
         type_str: "proc",
         size: 1,
-    });
+    })
+    .unwrap();
 
     //iterate through all inventory values of FrcStructureDescription and print type_str
     for struct_desc in inventory::iter::<FrcStructDesc> {
@@ -101,10 +501,384 @@ fn test_structures() {
     }
 }
 
+#[test]
+fn test_type_strings() {
+    let type_strings = [
+        "boolean", "int", "float", "double", "string", "json", "boolean[]", "int[]", "float[]",
+        "double[]", "string[]", "raw", "rpc", "msgpack", "structschema", "struct:Pose2d",
+        "struct:Pose2d[]", "proto:wpi.proto.ProtobufPose2d",
+    ];
+    for s in type_strings {
+        let type_string: FrcTypeString = s.parse().unwrap();
+        assert_eq!(type_string.to_string(), s);
+        let json = serde_json::to_string(&type_string).unwrap();
+        assert_eq!(serde_json::from_str::<FrcTypeString>(&json).unwrap(), type_string);
+    }
+    assert_eq!(
+        "struct:Pose2d[]".parse::<FrcTypeString>().unwrap(),
+        FrcTypeString::StructArray("Pose2d".to_owned())
+    );
+    assert_eq!(FrcTypeString::Json.frc_type(), FrcType::String);
+    assert_eq!(FrcTypeString::StructSchema.frc_type(), FrcType::Raw);
+    for s in ["bool[]", "struct:", "struct:[]", "proto:", "integer", "struct:A B"] {
+        assert!(matches!(
+            s.parse::<FrcTypeString>(),
+            Err(FrcValueError::InvalidTypeString(_))
+        ));
+    }
+
+    assert_eq!(FrcValue::from(vec![true]).type_string(), Some(FrcTypeString::BooleanArray));
+    assert_eq!(
+        FrcValue::from_struct(Meter { value: 1.0 }).type_string(),
+        Some(FrcTypeString::Struct("Meter".to_owned()))
+    );
+    assert_eq!(FrcValue::Void.type_string(), None);
+
+    // FrcType serializes to the same names and still accepts the old "bool[]"
+    for r#type in [FrcType::BoolArray, FrcType::Void, FrcType::StructArray, FrcType::Raw] {
+        let json = serde_json::to_string(&r#type).unwrap();
+        assert_eq!(serde_json::from_str::<FrcType>(&json).unwrap(), r#type);
+    }
+    assert_eq!(serde_json::to_string(&FrcType::BoolArray).unwrap(), r#""boolean[]""#);
+    assert_eq!(serde_json::from_str::<FrcType>(r#""bool[]""#).unwrap(), FrcType::BoolArray);
+    assert_eq!(serde_json::from_str::<FrcType>(r#""msgpack""#).unwrap(), FrcType::Raw);
+    assert_eq!(
+        serde_json::from_str::<FrcType>(r#""struct:Pose2d[]""#).unwrap(),
+        FrcType::StructArray
+    );
+}
+
+#[test]
+fn test_tagged_values() {
+    let values = vec![
+        FrcValue::Void,
+        FrcValue::Boolean(true),
+        FrcValue::Int(-3),
+        FrcValue::Double(0.1),
+        FrcValue::Float(0.1),
+        FrcValue::String("hi".to_owned()),
+        FrcValue::BooleanArray(vec![true, false]),
+        FrcValue::IntArray(vec![]),
+        FrcValue::FloatArray(vec![1.5, 0.1]),
+        FrcValue::DoubleArray(vec![0.1, 2.0]),
+        FrcValue::StringArray(vec!["a".to_owned()]),
+        FrcValue::Raw(Box::new(vec![0u8, 255, 7].into())),
+        FrcValue::from_struct(Meter { value: 2.5 }),
+        FrcValue::from_struct_slice(&[Meter { value: 1.0 }, Meter { value: -1.0 }]),
+    ];
+    for value in values {
+        let tagged = value.to_tagged();
+        let json = serde_json::to_string(&tagged).unwrap();
+        assert_eq!(serde_json::from_str::<FrcTaggedValue>(&json).unwrap(), tagged, "{}", json);
+    }
+
+    let json = serde_json::to_string(&FrcValue::from_struct(Meter { value: 2.5 }).to_tagged()).unwrap();
+    assert_eq!(json, r#"{"type":"struct:Meter","value":"AAAAAAAABEA="}"#);
+    // byte arrays and a value before the type are accepted too
+    let tagged: FrcTaggedValue =
+        serde_json::from_str(r#"{"value":[0,0,0,0,0,0,4,64],"type":"struct:Meter"}"#).unwrap();
+    assert_eq!(tagged.value.as_struct::<Meter>().unwrap(), Meter { value: 2.5 });
+    assert_eq!(tagged.r#type, FrcType::Struct);
+
+    // the names older versions tagged values with
+    let tagged: FrcTaggedValue = serde_json::from_str(r#"{"type":"bool[]","value":[true]}"#).unwrap();
+    assert_eq!(tagged.value, FrcValue::BooleanArray(vec![true]));
+    let tagged: FrcTaggedValue = serde_json::from_str(r#"{"type":"struct","value":[0,0,0,0,0,0,4,64]}"#).unwrap();
+    assert_eq!(tagged.value, FrcValue::Raw(Box::new(vec![0u8, 0, 0, 0, 0, 0, 4, 64].into())));
+
+    assert!(serde_json::from_str::<FrcTaggedValue>(r#"{"type":"struct:Unknown","value":""}"#).is_err());
+    assert!(serde_json::from_str::<FrcTaggedValue>(r#"{"type":"struct:Meter","value":"AAAA"}"#).is_err());
+
+    let type_string = FrcTypeString::Struct("Meter".to_owned());
+    let mut deserializer = serde_json::Deserializer::from_str("[0,0,0,0,0,0,4,64]");
+    let value = FrcValueSeed::new(Some(&type_string))
+        .deserialize(&mut deserializer)
+        .unwrap();
+    assert_eq!(value, FrcValue::from_struct(Meter { value: 2.5 }));
+}
+
+#[test]
+fn test_struct_arrays() {
+    let meters = vec![Meter { value: 1.0 }, Meter { value: -2.5 }, Meter { value: 4.0 }];
+    let value = FrcValue::from_struct_slice(&meters);
+    assert_eq!(value.get_type(), FrcType::StructArray);
+    assert_eq!(value.struct_array_len(), Some(3));
+    assert_eq!(value.as_bytes().unwrap().len(), 3 * Meter::SIZE);
+    assert_eq!(value.as_struct_vec::<Meter>().unwrap(), meters);
+    assert_eq!(value.clone().try_into_struct_vec::<Meter>().unwrap(), meters);
+    assert!(FrcValue::from_struct(meters[0]).try_into_struct_vec::<Meter>().is_err());
+    assert_eq!(
+        serde_json::to_string(&FrcType::StructArray).unwrap(),
+        r#""struct[]""#
+    );
+    assert_eq!(Meter::DESCRIPTION.array_type_string().to_string(), "struct:Meter[]");
+    assert_eq!(Meter::DESCRIPTION.type_string().to_string(), "struct:Meter");
+
+    let desc = FrcStructDescDB::get("Meter").unwrap();
+    let packed = value.as_bytes().unwrap().clone();
+    assert_eq!(
+        FrcValue::try_struct_array(desc, packed.clone()).unwrap(),
+        value
+    );
+    assert!(matches!(
+        FrcValue::try_struct_array(desc, packed.slice(1..)),
+        Err(FrcValueError::InvalidCast(_, "Meter", CastErrorReason::Type))
+    ));
+    assert_eq!(
+        FrcValue::from_struct_slice::<Meter>(&[]).as_struct_vec::<Meter>().unwrap(),
+        vec![]
+    );
+
+    let other = FrcValue::from_struct_slice(&[Meter { value: 3.0 }, Meter { value: 2.5 }, Meter { value: 0.0 }]);
+    assert_eq!(
+        value.lerp(&other, 0.5).unwrap().as_struct_vec::<Meter>().unwrap(),
+        vec![Meter { value: 2.0 }, Meter { value: 0.0 }, Meter { value: 2.0 }]
+    );
+
+    let mut buffer = FrcValueBuffer::new(FrcType::StructArray);
+    buffer.push(&value).unwrap();
+    buffer.push(&other).unwrap();
+    assert_eq!(buffer.len(), 2);
+    assert_eq!(buffer.get_value(1), Some(other));
+}
+
+#[test]
+fn test_dynamic_structure() {
+    FrcStructDescDB::add(FrcStructDesc {
+        schema: "float64 x; float64 y",
+        type_str: "DynTranslation",
+        size: 16,
+    })
+    .unwrap();
+    FrcStructDescDB::add(FrcStructDesc {
+        schema: "DynTranslation trans; float32 angle",
+        type_str: "DynPose",
+        size: 20,
+    })
+    .unwrap();
+    FrcStructDescDB::add(FrcStructDesc {
+        schema: "DynPose pose; int16 val[3]; bool ok; char name[4]; uint8 id",
+        type_str: "DynRobot",
+        size: 32,
+    })
+    .unwrap();
+    let desc = FrcStructDescDB::get("DynRobot").unwrap();
+    let mut robot = structure::DynamicStructure::try_new(desc, BytesMut::zeroed(32)).unwrap();
+    assert!(matches!(
+        structure::DynamicStructure::try_new(desc, BytesMut::zeroed(31)),
+        Err(FrcValueError::SizeMismatch { expected: 32, actual: 31 })
+    ));
+
+    robot.set("pose.trans.x", FrcValue::Double(1.5)).unwrap();
+    robot.set("pose.angle", FrcValue::Double(0.5)).unwrap();
+    robot.set("val", FrcValue::IntArray(vec![1, -2, 3])).unwrap();
+    robot.set("val[1]", FrcValue::Int(7)).unwrap();
+    robot.set("ok", FrcValue::Boolean(true)).unwrap();
+    robot.set("name", FrcValue::String("bot".to_owned())).unwrap();
+    robot.set("id", FrcValue::Double(4.0)).unwrap();
+
+    assert_eq!(robot.get("pose.trans.x"), Some(FrcValue::Double(1.5)));
+    assert_eq!(robot.get("pose.angle"), Some(FrcValue::Float(0.5)));
+    assert_eq!(robot.get("val"), Some(FrcValue::IntArray(vec![1, 7, 3])));
+    assert_eq!(robot.get("val[2]"), Some(FrcValue::Int(3)));
+    assert_eq!(robot.get("val[3]"), None);
+    assert_eq!(robot.get("name"), Some(FrcValue::String("bot".to_owned())));
+    assert_eq!(robot.get("missing"), None);
+
+    assert!(matches!(
+        robot.set("id", FrcValue::Int(256)),
+        Err(FrcValueError::InvalidCast(FrcType::Int, "uint8", CastErrorReason::Overflow))
+    ));
+    assert!(matches!(
+        robot.set("val", FrcValue::IntArray(vec![1])),
+        Err(FrcValueError::InvalidCast(_, "int16", CastErrorReason::Type))
+    ));
+    assert!(matches!(
+        robot.set("pose.trans.z", FrcValue::Double(1.0)),
+        Err(FrcValueError::UnknownField(_))
+    ));
+
+    let names = robot.iter().map(|(name, _)| name).collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec!["pose.trans.x", "pose.trans.y", "pose.angle", "val", "ok", "name", "id"]
+    );
+    assert_eq!(robot.iter().last(), Some(("id", FrcValue::Int(4))));
+    assert_eq!(robot.to_value().get_type(), FrcType::Struct);
+}
+
+#[test]
+fn test_enums() {
+    use crate as frc_values;
+
+    const SCHEMA: &str = "enum {off=0, on=1, auto=-1} int8 mode; enum{a=1,b=2} float32 not_enum; uint8 x";
+    let fields = structure::parse_schema_fields(SCHEMA, "", 0).unwrap();
+    assert_eq!(fields.len(), 3);
+    assert!(structure::parse_schema_fields("enum {a=0} int8 x; float64 x", "", 0).is_err());
+    assert_eq!(
+        fields[0].variants,
+        Some(vec![
+            ("off".to_owned(), 0),
+            ("on".to_owned(), 1),
+            ("auto".to_owned(), -1)
+        ])
+    );
+    assert_eq!(fields[1].variants, None);
+    assert_eq!(fields[2].offset, 5);
+
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    #[repr(u8)]
+    enum DriveMode {
+        Idle,
+        Teleop = 4,
+        Auto,
+    }
+    assert_eq!(
+        <DriveMode as FrcStructure>::SCHEMA,
+        "enum {Idle=0, Teleop=4, Auto=5} uint8 value"
+    );
+    assert_eq!(
+        <DriveMode as FrcStructure>::ENUM_SPEC,
+        Some("enum {Idle=0, Teleop=4, Auto=5} uint8")
+    );
+    assert_eq!(<DriveMode as FrcStructure>::SIZE, 1);
+
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    struct EnumTestStruct {
+        mode: DriveMode,
+        speed: f64,
+    }
+    assert_eq!(
+        <EnumTestStruct as FrcStructure>::SCHEMA,
+        "enum {Idle=0, Teleop=4, Auto=5} uint8 mode; float64 speed"
+    );
+    let value = FrcValue::from_struct(EnumTestStruct {
+        mode: DriveMode::Auto,
+        speed: 2.0,
+    });
+    assert_eq!(
+        value.as_struct::<EnumTestStruct>().unwrap().mode,
+        DriveMode::Auto
+    );
+    assert_eq!(
+        FrcValue::from_struct_slice(&[DriveMode::Teleop]).as_bytes().unwrap()[..],
+        [4]
+    );
+    // unknown values aren't unpacked into any variant
+    assert!(!DriveMode::is_valid(&[9]));
+    let mut unknown = value.as_bytes().unwrap().to_vec();
+    unknown[0] = 9;
+    let unknown = FrcValue::Struct((&EnumTestStruct::DESCRIPTION).into(), Box::new(unknown.into()));
+    assert!(unknown.as_struct::<EnumTestStruct>().is_err());
+    let unknown = FrcValue::StructArray((&DriveMode::DESCRIPTION).into(), Box::new(vec![4u8, 9].into()));
+    assert!(unknown.as_struct_vec::<DriveMode>().is_err());
+
+    let FrcValue::Struct(desc, bytes) = value else {
+        panic!("not a struct");
+    };
+    let mut dynamic = structure::DynamicStructure::try_new(desc, BytesMut::from(&bytes[..])).unwrap();
+    assert_eq!(dynamic.get("mode"), Some(FrcValue::Int(5)));
+    assert_eq!(dynamic.variant("mode"), Some("Auto"));
+    assert_eq!(dynamic.variants("mode").unwrap().len(), 3);
+    dynamic
+        .set("mode", FrcValue::String("Teleop".to_owned()))
+        .unwrap();
+    assert_eq!(dynamic.variant("mode"), Some("Teleop"));
+    assert!(dynamic
+        .set("mode", FrcValue::String("Unknown".to_owned()))
+        .is_err());
+    assert_eq!(dynamic.variant("speed"), None);
+}
+
+#[test]
+fn test_bitfields() {
+    use crate as frc_values;
+
+    const SCHEMA: &str = "int32 a:4; int32 b:3; int8 c:2; bool d:1; int16 e; uint8 f:8; uint8 g:1";
+    let fields = structure::parse_schema_fields(SCHEMA, "", 0).unwrap();
+    let layout = fields
+        .iter()
+        .map(|field| {
+            let bits = field.bits.unwrap_or(structure::Bitfield {
+                storage: 0,
+                shift: 0,
+                width: 0,
+            });
+            (field.name.as_str(), field.offset, bits.shift)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        layout,
+        [
+            ("a", 0, 0),
+            ("b", 0, 4),
+            ("c", 4, 0),
+            ("d", 4, 2),
+            ("e", 5, 0),
+            ("f", 7, 0),
+            ("g", 8, 0)
+        ]
+    );
+
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    struct BitfieldTestStruct {
+        #[frc(bits = 4)]
+        a: i32,
+        #[frc(bits = 3)]
+        b: u32,
+        #[frc(bits = 1)]
+        flag: bool,
+        speed: f32,
+        #[frc(bits = 5)]
+        c: i8,
+    }
+    assert_eq!(
+        <BitfieldTestStruct as FrcStructure>::SCHEMA,
+        "int32 a:4; uint32 b:3; bool flag:1; float32 speed; int8 c:5"
+    );
+    assert_eq!(<BitfieldTestStruct as FrcStructure>::SIZE, 9);
+    let original = BitfieldTestStruct {
+        a: -3,
+        b: 5,
+        flag: true,
+        speed: 1.5,
+        c: -16,
+    };
+    let value = FrcValue::from_struct(original);
+    assert_eq!(value.as_bytes().unwrap()[..4], [0b1101_1101, 0, 0, 0]);
+    assert_eq!(value.as_struct::<BitfieldTestStruct>().unwrap(), original);
+
+    let FrcValue::Struct(desc, bytes) = value else {
+        panic!("not a struct");
+    };
+    let mut dynamic = structure::DynamicStructure::try_new(desc, BytesMut::from(&bytes[..])).unwrap();
+    assert_eq!(dynamic.get("a"), Some(FrcValue::Int(-3)));
+    assert_eq!(dynamic.get("b"), Some(FrcValue::Int(5)));
+    assert_eq!(dynamic.get("flag"), Some(FrcValue::Boolean(true)));
+    assert_eq!(dynamic.get("c"), Some(FrcValue::Int(-16)));
+    dynamic.set("a", FrcValue::Int(7)).unwrap();
+    dynamic.set("flag", FrcValue::Boolean(false)).unwrap();
+    assert!(dynamic.set("a", FrcValue::Int(8)).is_err());
+    assert!(dynamic.set("b", FrcValue::Int(-1)).is_err());
+    assert_eq!(dynamic.get("b"), Some(FrcValue::Int(5)));
+    let updated = dynamic
+        .to_value()
+        .as_struct::<BitfieldTestStruct>()
+        .unwrap();
+    assert_eq!(
+        updated,
+        BitfieldTestStruct {
+            a: 7,
+            flag: false,
+            ..original
+        }
+    );
+}
+
 #[test]
 fn test_schema() {
     const SCHEMA: &str = "enum {a=1, b=2} int8 val[3]";
-    let fields = structure::parse_schema_toplevel(SCHEMA);
+    let fields = structure::parse_schema_toplevel(SCHEMA).unwrap();
     assert_eq!(fields.len(), 1);
     assert_eq!(
         fields[0],
@@ -119,13 +893,15 @@ fn test_schema_advanced() {
         schema: "double value",
         type_str: "Rotation2d",
         size: 8,
-    });
+    })
+    .unwrap();
     FrcStructDescDB::add(FrcStructDesc {
         schema: "double x; double y",
         type_str: "Translation2d",
         size: 16,
-    });
-    let fields = structure::parse_schema_toplevel(SCHEMA);
+    })
+    .unwrap();
+    let fields = structure::parse_schema_toplevel(SCHEMA).unwrap();
     assert_eq!(fields.len(), 3);
     assert_eq!(
         fields,
@@ -147,4 +923,967 @@ fn test_schema_advanced() {
             )
         ]
     );
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_schema_errors() {
+    use crate as frc_values;
+    use crate::{SchemaError, SchemaErrorReason};
+
+    let error = |schema: &'static str, size: usize| {
+        FrcStructDesc {
+            schema,
+            type_str: "SchemaErrorTest",
+            size,
+        }
+        .validate()
+        .unwrap_err()
+    };
+    assert_eq!(
+        error("double x; int32 y$", 12),
+        SchemaError {
+            position: 17,
+            token: "$".to_owned(),
+            reason: SchemaErrorReason::InvalidToken
+        }
+    );
+    assert_eq!(
+        error("double x; doubel y", 16),
+        SchemaError {
+            position: 10,
+            token: "doubel".to_owned(),
+            reason: SchemaErrorReason::UnresolvedStruct
+        }
+    );
+    assert_eq!(error("5 x", 8).reason, SchemaErrorReason::UnknownType);
+    assert_eq!(
+        error("double x; float x", 12),
+        SchemaError {
+            position: 16,
+            token: "x".to_owned(),
+            reason: SchemaErrorReason::DuplicateField
+        }
+    );
+    assert_eq!(error("double x y", 8).reason, SchemaErrorReason::Syntax);
+    assert_eq!(error("double x[0]", 0).reason, SchemaErrorReason::Syntax);
+    assert_eq!(error("float x:3", 4).reason, SchemaErrorReason::InvalidBitfield);
+    assert_eq!(error("int8 x:9", 1).reason, SchemaErrorReason::InvalidBitfield);
+    assert_eq!(
+        error("double x; int8 y[3]", 10).reason,
+        SchemaErrorReason::SizeMismatch {
+            expected: 10,
+            actual: 11
+        }
+    );
+    assert_eq!(
+        error("int64 x[4000000000000000000]", 8),
+        SchemaError {
+            position: 0,
+            token: "int64".to_owned(),
+            reason: SchemaErrorReason::TooLarge
+        }
+    );
+    assert_eq!(
+        error("Meter m[4000000000000000000]", 8).reason,
+        SchemaErrorReason::TooLarge
+    );
+    assert_eq!(
+        error("Meter m[1000000000000]", 8).reason,
+        SchemaErrorReason::SizeMismatch {
+            expected: 8,
+            actual: 8_000_000_000_000
+        }
+    );
+    // huge arrays of nested structs are checked without expanding them
+    let huge: &'static FrcStructDesc = Box::leak(Box::new(FrcStructDesc {
+        schema: "Meter m[1000000000000]",
+        type_str: "SchemaErrorTest",
+        size: 8_000_000_000_000,
+    }));
+    huge.validate().unwrap();
+    assert!(matches!(
+        structure::DynamicStructure::try_new(huge, BytesMut::zeroed(8)),
+        Err(FrcValueError::SizeMismatch { .. })
+    ));
+
+    let desc: &'static FrcStructDesc = Box::leak(Box::new(FrcStructDesc {
+        schema: "double x; double x",
+        type_str: "SchemaErrorTest",
+        size: 16,
+    }));
+    assert!(matches!(
+        structure::DynamicStructure::try_new(desc, BytesMut::zeroed(16)),
+        Err(FrcValueError::InvalidSchema(SchemaError {
+            reason: SchemaErrorReason::DuplicateField,
+            ..
+        }))
+    ));
+
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    struct ValidatedTestStruct {
+        meters: [Meter; 2],
+        flag: bool,
+        #[frc(bits = 3)]
+        bits: u8,
+    }
+    <ValidatedTestStruct as FrcStructure>::DESCRIPTION
+        .validate()
+        .unwrap();
+}
+
+#[test]
+fn test_owned_struct_descs() {
+    use crate::structure::FrcOwnedStructDesc;
+
+    assert!(FrcStructDescDB::get("Meter").is_some());
+    assert_eq!(
+        FrcStructDescDB::get_owned("Meter").unwrap().schema,
+        "float64 value;"
+    );
+
+    let odometry = FrcOwnedStructDesc::new(
+        "RuntimeOdometry",
+        "RuntimeWheel left; RuntimeWheel right",
+        16,
+    );
+    assert_eq!(
+        odometry.validate().unwrap_err().reason,
+        crate::SchemaErrorReason::UnresolvedStruct
+    );
+    assert!(FrcStructDescDB::register(FrcOwnedStructDesc::new(
+        "RuntimeWheel",
+        "float32 position",
+        4
+    ))
+    .unwrap()
+    .is_none());
+    assert!(FrcStructDescDB::contains("RuntimeWheel"));
+    assert!(FrcStructDescDB::get("RuntimeWheel").is_none());
+    assert!(odometry.validate().is_err());
+
+    let replaced = FrcStructDescDB::register(FrcOwnedStructDesc::new(
+        "RuntimeWheel",
+        "float32 position; float32 velocity",
+        8,
+    ))
+    .unwrap()
+    .unwrap();
+    assert_eq!(replaced.size, 4);
+    odometry.validate().unwrap();
+
+    assert_eq!(FrcStructDescDB::remove("RuntimeWheel").unwrap().size, 8);
+    assert!(!FrcStructDescDB::contains("RuntimeWheel"));
+    assert!(FrcStructDescDB::remove("RuntimeWheel").is_none());
+    assert!(FrcStructDescDB::remove("Meter").is_none());
+    assert!(FrcStructDescDB::contains("Meter"));
+
+    // malformed schemas are rejected when they're registered instead of when they're decoded
+    assert!(FrcStructDescDB::register(FrcOwnedStructDesc::new(
+        "RuntimeHuge",
+        "int64 x[4000000000000000000]",
+        8
+    ))
+    .is_err());
+    assert!(!FrcStructDescDB::contains("RuntimeHuge"));
+    assert!(FrcStructDescDB::add(FrcStructDesc {
+        schema: "double x; double x",
+        type_str: "RuntimeDuplicate",
+        size: 16,
+    })
+    .is_err());
+    assert!(!FrcStructDescDB::contains("RuntimeDuplicate"));
+
+    // values of owned descriptions decode like the ones of static descriptions
+    FrcStructDescDB::register(FrcOwnedStructDesc::new("RuntimeSample", "float64 t; int16 n", 10))
+        .unwrap();
+    let tagged: FrcTaggedValue = serde_json::from_str(
+        r#"{"type":"struct:RuntimeSample","value":[0,0,0,0,0,0,240,63,2,0]}"#,
+    )
+    .unwrap();
+    let FrcValue::Struct(desc, bytes) = &tagged.value else {
+        panic!("not a struct");
+    };
+    assert!(desc.as_static().is_none());
+    let sample = structure::DynamicStructure::try_new(desc.clone(), BytesMut::from(&bytes[..])).unwrap();
+    assert_eq!(sample.get("t"), Some(FrcValue::Double(1.0)));
+    assert_eq!(sample.get("n"), Some(FrcValue::Int(2)));
+    let untagged = FrcValue::from_untagged(Some(&desc.type_string()), FrcValue::Raw(bytes.clone()));
+    assert_eq!(untagged.unwrap(), tagged.value);
+}
+
+#[test]
+fn test_schema_compatibility() {
+    use crate::structure::SchemaCompatibility;
+
+    let desc = |schema: &'static str, size: usize| FrcStructDesc {
+        schema,
+        type_str: "CompatibilityTest",
+        size,
+    };
+    let old = desc("float64 x; enum {a=0, b=1} int8 mode", 9);
+    assert_eq!(
+        old.compatibility(&desc("double x;enum{a=0,b=1} int8 mode;", 9)),
+        SchemaCompatibility::Identical
+    );
+    assert_eq!(
+        old.compatibility(&desc("float64 y; enum {a=0, c=1} int8 mode", 9)),
+        SchemaCompatibility::LayoutCompatible
+    );
+    assert_eq!(
+        old.compatibility(&desc("float64 x; enum {a=0, b=1} int8 mode; bool extra", 10)),
+        SchemaCompatibility::AppendOnly
+    );
+    assert_eq!(
+        old.compatibility(&desc("float32 x; int8 mode", 5)),
+        SchemaCompatibility::Incompatible
+    );
+    assert_eq!(
+        old.compatibility(&desc("float64 x", 8)),
+        SchemaCompatibility::Incompatible
+    );
+    assert_eq!(
+        old.compatibility(&desc("float64 x; int8 mode", 12)),
+        SchemaCompatibility::Incompatible
+    );
+
+    FrcStructDescDB::add(old).unwrap();
+    FrcStructDescDB::add(old).unwrap();
+    assert!(matches!(
+        FrcStructDescDB::add(desc("float64 x; enum {a=0, b=1} int8 mode; bool extra", 10)),
+        Err(FrcValueError::SchemaConflict(_, SchemaCompatibility::AppendOnly))
+    ));
+    assert_eq!(FrcStructDescDB::get("CompatibilityTest"), Some(&old));
+}
+
+#[test]
+fn test_schema_normalization() {
+    use std::hash::BuildHasher;
+    use structure::{normalize_schema, schema_fingerprint};
+
+    assert_eq!(
+        normalize_schema(" double x;float  y[2] ;enum{a=1,b=-2} int8 mode; uint8 flag:1;").unwrap(),
+        "float64 x; float32 y[2]; enum {a=1, b=-2} int8 mode; uint8 flag:1"
+    );
+    assert_eq!(normalize_schema("").unwrap(), "");
+    assert!(normalize_schema("double x y").is_err());
+
+    assert_eq!(
+        schema_fingerprint("Normalized", " double x; float y;"),
+        schema_fingerprint("Normalized", "float64 x; float32 y")
+    );
+    assert_ne!(
+        schema_fingerprint("Normalized", "float64 x"),
+        schema_fingerprint("Normalized", "float64 y")
+    );
+    assert_ne!(
+        schema_fingerprint("Normalized", "float64 x"),
+        schema_fingerprint("Other", "float64 x")
+    );
+    // fingerprints must not change between releases
+    assert_eq!(schema_fingerprint("", ""), 0xaf63bd4c8601b7df);
+
+    let fingerprint = Meter::DESCRIPTION.fingerprint();
+    assert_eq!(
+        fingerprint,
+        schema_fingerprint("Meter", "float64 value")
+    );
+    assert_eq!(
+        FrcStructDescDB::get_by_fingerprint(fingerprint),
+        FrcStructDescDB::get("Meter")
+    );
+
+    let hash = |value: &FrcValue| fxhash::FxBuildHasher::default().hash_one(value);
+    let aliased: &'static FrcStructDesc = Box::leak(Box::new(FrcStructDesc {
+        schema: "double value",
+        type_str: "Meter",
+        size: 8,
+    }));
+    let bytes = Box::new(bytes::Bytes::from_static(&[0; 8]));
+    assert_eq!(
+        hash(&FrcValue::Struct(aliased.into(), bytes.clone())),
+        hash(&FrcValue::Struct((&Meter::DESCRIPTION).into(), bytes))
+    );
+}
+
+#[test]
+fn test_codegen() {
+    use crate as frc_values;
+    use structure::{FrcOwnedStructDesc, SchemaCompatibility};
+
+    let trans = FrcOwnedStructDesc::from_schema("CodegenTrans", "double x; double y[2]").unwrap();
+    assert_eq!(trans.size, 24);
+    FrcStructDescDB::register(trans).unwrap();
+    let pose = FrcOwnedStructDesc::from_schema(
+        "CodegenPose",
+        "CodegenTrans trans; float rot; enum {a=1, b=2} int8 mode; uint8 type:3; bool f:1",
+    )
+    .unwrap();
+    assert_eq!(
+        crate::codegen::generate_structs(std::slice::from_ref(&pose)).unwrap(),
+        r#"/// `double x; double y[2]`
+#[derive(Debug, Clone, Copy, PartialEq, frc_values_macros::FrcStructure)]
+pub struct CodegenTrans {
+    pub x: f64,
+    pub y: [f64; 2],
+}
+
+/// `enum {a=1, b=2} int8`
+#[derive(Debug, Clone, Copy, PartialEq, frc_values_macros::FrcStructure)]
+#[repr(i8)]
+#[allow(non_camel_case_types)]
+pub enum CodegenPoseMode {
+    a = 1,
+    b = 2,
+}
+
+/// `CodegenTrans trans; float rot; enum {a=1, b=2} int8 mode; uint8 type:3; bool f:1`
+#[derive(Debug, Clone, Copy, PartialEq, frc_values_macros::FrcStructure)]
+pub struct CodegenPose {
+    pub trans: CodegenTrans,
+    pub rot: f32,
+    pub mode: CodegenPoseMode,
+    #[frc(bits = 3)]
+    pub r#type: u8,
+    #[frc(bits = 1)]
+    pub f: bool,
+}
+"#
+    );
+
+    // the generated code from above
+    #[derive(Debug, Clone, Copy, PartialEq, frc_values_macros::FrcStructure)]
+    pub struct CodegenTrans {
+        pub x: f64,
+        pub y: [f64; 2],
+    }
+    #[derive(Debug, Clone, Copy, PartialEq, frc_values_macros::FrcStructure)]
+    #[repr(i8)]
+    #[allow(non_camel_case_types)]
+    pub enum CodegenPoseMode {
+        a = 1,
+        b = 2,
+    }
+    #[derive(Debug, Clone, Copy, PartialEq, frc_values_macros::FrcStructure)]
+    pub struct CodegenPose {
+        pub trans: CodegenTrans,
+        pub rot: f32,
+        pub mode: CodegenPoseMode,
+        #[frc(bits = 3)]
+        pub r#type: u8,
+        #[frc(bits = 1)]
+        pub f: bool,
+    }
+    let generated = FrcOwnedStructDesc::from(&<CodegenPose as FrcStructure>::DESCRIPTION);
+    assert_eq!(
+        pose.compatibility(&generated),
+        SchemaCompatibility::Identical
+    );
+    let value = FrcValue::from_struct(CodegenPose {
+        trans: CodegenTrans {
+            x: 1.0,
+            y: [2.0, 3.0],
+        },
+        rot: 0.5,
+        mode: CodegenPoseMode::b,
+        r#type: 5,
+        f: true,
+    });
+    let FrcValue::Struct(_, bytes) = &value else {
+        panic!("not a struct");
+    };
+    assert_eq!(bytes.len(), pose.size);
+    assert_eq!(bytes[pose.size - 1], 0b1101);
+
+    let invalid_name = FrcOwnedStructDesc::new("Bad-Name", "double x", 8);
+    assert!(crate::codegen::generate_structs(&[invalid_name]).is_err());
+}
+
+#[test]
+fn test_schema_closure() {
+    use crate as frc_values;
+    use structure::FrcOwnedStructDesc;
+
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    struct ClosureInner {
+        meter: Meter,
+    }
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    struct ClosureOuter {
+        inner: ClosureInner,
+        inners: [ClosureInner; 2],
+        meter: Meter,
+    }
+    let closure = <ClosureOuter as FrcStructure>::DESCRIPTION
+        .schema_closure()
+        .unwrap();
+    assert_eq!(
+        closure,
+        vec![
+            (
+                "/.schema/struct:Meter".to_owned(),
+                FrcValue::Raw(Box::new(bytes::Bytes::from_static(b"float64 value;")))
+            ),
+            (
+                "/.schema/struct:ClosureInner".to_owned(),
+                FrcValue::Raw(Box::new(bytes::Bytes::from_static(b"Meter meter")))
+            ),
+            (
+                "/.schema/struct:ClosureOuter".to_owned(),
+                FrcValue::Raw(Box::new(bytes::Bytes::from_static(b"ClosureInner inner; ClosureInner inners[2]; Meter meter")))
+            ),
+        ]
+    );
+    // published with the structschema type
+    assert_eq!(FrcTypeString::StructSchema.to_string(), "structschema");
+
+    let recursive_error = |result| {
+        matches!(
+            result,
+            Err(FrcValueError::InvalidSchema(crate::SchemaError {
+                reason: crate::SchemaErrorReason::RecursiveStruct,
+                ..
+            }))
+        )
+    };
+    FrcStructDescDB::register(FrcOwnedStructDesc::new("RecursiveA", "float64 x", 8)).unwrap();
+    FrcStructDescDB::register(FrcOwnedStructDesc::new("RecursiveB", "RecursiveA a", 8)).unwrap();
+    // a struct can't be replaced by one that contains itself
+    let recursive = FrcOwnedStructDesc::new("RecursiveA", "RecursiveB b", 8);
+    assert!(recursive_error(recursive.schema_closure().map(|_| ())));
+    assert_eq!(
+        recursive.validate().unwrap_err().reason,
+        crate::SchemaErrorReason::RecursiveStruct
+    );
+    assert!(recursive_error(FrcStructDescDB::register(recursive).map(|_| ())));
+    assert_eq!(
+        FrcStructDescDB::get_owned("RecursiveA").unwrap().schema,
+        "float64 x"
+    );
+
+    let recursive = FrcOwnedStructDesc::new("RecursiveSelf", "RecursiveSelf a", 8);
+    assert!(recursive_error(FrcStructDescDB::register(recursive.clone()).map(|_| ())));
+    assert!(!FrcStructDescDB::contains("RecursiveSelf"));
+    assert_eq!(
+        recursive.validate().unwrap_err().reason,
+        crate::SchemaErrorReason::RecursiveStruct
+    );
+    assert!(recursive_error(
+        structure::DynamicStructure::try_new(recursive, BytesMut::zeroed(8)).map(|_| ())
+    ));
+}
+
+#[cfg(feature = "json-casting")]
+#[test]
+fn test_struct_json() {
+    use crate as frc_values;
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    #[repr(u8)]
+    enum JsonMode {
+        Off,
+        On,
+    }
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    struct JsonTestStruct {
+        meter: Meter,
+        meters: [Meter; 2],
+        mode: JsonMode,
+        ints: [i16; 3],
+        flag: bool,
+        name: [char; 4],
+    }
+    let original = JsonTestStruct {
+        meter: Meter { value: 1.5 },
+        meters: [Meter { value: 2.0 }, Meter { value: 3.0 }],
+        mode: JsonMode::On,
+        ints: [1, -2, 3],
+        flag: true,
+        name: ['b', 'o', 't', '\0'],
+    };
+    let value = FrcValue::from_struct(original);
+    let expected = json!({
+        "meter": {"value": 1.5},
+        "meters": [{"value": 2.0}, {"value": 3.0}],
+        "mode": "On",
+        "ints": [1, -2, 3],
+        "flag": true,
+        "name": "bot"
+    });
+    assert_eq!(value.struct_to_json().unwrap(), expected);
+    assert_eq!(serde_json::Value::from(value.clone()), expected);
+
+    let desc = &<JsonTestStruct as FrcStructure>::DESCRIPTION;
+    let round_trip = FrcValue::struct_from_json(desc, &expected).unwrap();
+    assert_eq!(round_trip.as_struct::<JsonTestStruct>().unwrap(), original);
+
+    let mut missing = expected.clone();
+    missing["meters"][1].as_object_mut().unwrap().remove("value");
+    assert!(matches!(
+        FrcValue::struct_from_json(desc, &missing),
+        Err(FrcValueError::MissingField(path)) if path == "meters[1].value"
+    ));
+    let mut unknown = expected.clone();
+    unknown["meter"]["extra"] = json!(1);
+    assert!(matches!(
+        FrcValue::struct_from_json(desc, &unknown),
+        Err(FrcValueError::UnknownField(path)) if path == "meter.extra"
+    ));
+    let mut out_of_range = expected.clone();
+    out_of_range["ints"] = json!([1, 2, 40000]);
+    assert!(FrcValue::struct_from_json(desc, &out_of_range).is_err());
+    let mut wrong_length = expected;
+    wrong_length["ints"] = json!([1, 2]);
+    assert!(FrcValue::struct_from_json(desc, &wrong_length).is_err());
+
+    // uint64 values above i64::MAX survive the round trip
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    struct JsonU64Struct {
+        big: u64,
+        bigs: [u64; 2],
+    }
+    let original = JsonU64Struct {
+        big: u64::MAX,
+        bigs: [1, u64::MAX],
+    };
+    let value = FrcValue::from_struct(original);
+    let json = value.struct_to_json().unwrap();
+    assert_eq!(json, json!({"big": u64::MAX, "bigs": [1, u64::MAX]}));
+    let round_trip =
+        FrcValue::struct_from_json(&<JsonU64Struct as FrcStructure>::DESCRIPTION, &json).unwrap();
+    assert_eq!(round_trip.as_struct::<JsonU64Struct>().unwrap(), original);
+    let FrcValue::Struct(desc, bytes) = value else {
+        panic!("not a struct");
+    };
+    let dynamic = structure::DynamicStructure::try_new(desc, BytesMut::from(&bytes[..])).unwrap();
+    assert_eq!(dynamic.get("big"), Some(FrcValue::Double(u64::MAX as f64)));
+    assert_eq!(
+        dynamic.get("bigs"),
+        Some(FrcValue::DoubleArray(vec![1.0, u64::MAX as f64]))
+    );
+
+    FrcStructDescDB::register(structure::FrcOwnedStructDesc::new("RuntimeJson", "float64 t; uint8 n", 9))
+        .unwrap();
+    let desc = FrcStructDescDB::get_handle("RuntimeJson").unwrap();
+    let value = FrcValue::struct_from_json(desc, &json!({"t": 0.5, "n": 3})).unwrap();
+    assert_eq!(value.struct_to_json().unwrap(), json!({"t": 0.5, "n": 3}));
+
+    assert_eq!(
+        serde_json::Value::from(FrcValue::from_struct_slice(&[Meter { value: 1.0 }])),
+        json!([{"value": 1.0}])
+    );
+}
+
+#[test]
+fn test_derive_shapes() {
+    use crate as frc_values;
+    use frc_units::angle::Degree;
+
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    struct TupleStruct(f64, Meter, [u8; 2]);
+    assert_eq!(
+        <TupleStruct as FrcStructure>::SCHEMA,
+        "float64 _0; Meter _1; uint8 _2[2]"
+    );
+    let tuple = TupleStruct(1.0, Meter { value: 2.0 }, [3, 4]);
+    assert_eq!(FrcValue::from_struct(tuple).as_struct::<TupleStruct>().unwrap(), tuple);
+
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    struct Pair<T> {
+        first: T,
+        second: T,
+    }
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    struct Holder {
+        pair: Pair<Meter>,
+        pairs: [Pair<TupleStruct>; 2],
+    }
+    assert_eq!(<Pair<Meter> as FrcStructure>::TYPE, "Pair_Meter");
+    assert_eq!(<Pair<Meter> as FrcStructure>::SCHEMA, "Meter first; Meter second");
+    assert_eq!(<Pair<Meter> as FrcStructure>::SIZE, 16);
+    assert_eq!(
+        <Pair<Pair<Meter>> as FrcStructure>::SCHEMA,
+        "Pair_Meter first; Pair_Meter second"
+    );
+    assert_eq!(
+        <Holder as FrcStructure>::SCHEMA,
+        "Pair_Meter pair; Pair_TupleStruct pairs[2]"
+    );
+    let holder = Holder {
+        pair: Pair {
+            first: Meter { value: 1.0 },
+            second: Meter { value: -1.0 },
+        },
+        pairs: [Pair {
+            first: tuple,
+            second: TupleStruct(5.0, Meter { value: 6.0 }, [7, 8]),
+        }; 2],
+    };
+    let value = FrcValue::from_struct(holder);
+    assert_eq!(value.as_struct::<Holder>().unwrap(), holder);
+    assert_eq!(
+        <Holder as FrcStructure>::DESCRIPTION
+            .schema_closure()
+            .unwrap()
+            .len(),
+        5
+    );
+
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    struct UnitStruct {
+        #[frc(unit)]
+        distance: frc_units::distance::Meter,
+        #[frc(unit = "u64")]
+        elapsed: frc_units::time::Microsecond,
+        #[frc(unit)]
+        headings: [Degree; 2],
+    }
+    assert_eq!(
+        <UnitStruct as FrcStructure>::SCHEMA,
+        "float64 distance; uint64 elapsed; float64 headings[2]"
+    );
+    let units = UnitStruct {
+        distance: 1.5.into(),
+        elapsed: 20.0.into(),
+        headings: [90.0.into(), 180.0.into()],
+    };
+    let value = FrcValue::from_struct(units);
+    assert_eq!(value.as_struct::<UnitStruct>().unwrap(), units);
+    let FrcValue::Struct(desc, bytes) = value else {
+        panic!("not a struct");
+    };
+    let dynamic = structure::DynamicStructure::try_new(desc, BytesMut::from(&bytes[..])).unwrap();
+    assert_eq!(dynamic.get("elapsed"), Some(FrcValue::Int(20)));
+    assert_eq!(dynamic.get("headings[1]"), Some(FrcValue::Double(180.0)));
+}
+
+#[test]
+fn test_derive_attributes() {
+    use crate as frc_values;
+
+    #[derive(Debug, PartialEq, Clone, Copy, Default, frc_values_macros::FrcStructure)]
+    struct Wrapper<T> {
+        inner: T,
+    }
+    #[derive(Debug, PartialEq, Clone, Copy, Default, frc_values_macros::FrcStructure)]
+    #[frc(rename = "AttrInner")]
+    struct AttrNested {
+        value: f64,
+    }
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    #[frc(rename = "AttrMode")]
+    #[repr(u8)]
+    enum Mode {
+        Disabled,
+        Enabled,
+    }
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    #[frc(rename = "RenamedStruct")]
+    struct AttrStruct {
+        #[frc(rename = "type")]
+        kind: u8,
+        #[frc(skip)]
+        cached: u32,
+        #[frc(type = "float32")]
+        x: f64,
+        #[frc(type = "float", rename = "ys")]
+        y: [f64; 2],
+        #[frc(type = "int16")]
+        count: i64,
+        mode: Mode,
+        #[frc(schema_only)]
+        nested: Wrapper<AttrNested>,
+    }
+    assert_eq!(<Mode as FrcStructure>::TYPE, "AttrMode");
+    assert_eq!(<Wrapper<AttrNested> as FrcStructure>::TYPE, "Wrapper_AttrInner");
+    assert_eq!(<AttrStruct as FrcStructure>::TYPE, "RenamedStruct");
+    assert_eq!(
+        <AttrStruct as FrcStructure>::SCHEMA,
+        "uint8 type; float32 x; float32 ys[2]; int16 count; enum {Disabled=0, Enabled=1} uint8 mode"
+    );
+    assert_eq!(<AttrStruct as FrcStructure>::SIZE, 16);
+    assert!(FrcStructDescDB::get("RenamedStruct").is_some());
+    assert!(FrcStructDescDB::get("Wrapper_AttrInner").is_some());
+
+    let original = AttrStruct {
+        kind: 3,
+        cached: 42,
+        x: 1.5,
+        y: [0.25, -2.0],
+        count: -300,
+        mode: Mode::Enabled,
+        nested: Wrapper {
+            inner: AttrNested { value: 1.0 },
+        },
+    };
+    let value = FrcValue::from_struct(original);
+    assert_eq!(
+        value.as_struct::<AttrStruct>().unwrap(),
+        AttrStruct {
+            cached: 0,
+            nested: Wrapper::default(),
+            ..original
+        }
+    );
+    let FrcValue::Struct(desc, bytes) = value else {
+        panic!("not a struct");
+    };
+    let dynamic = structure::DynamicStructure::try_new(desc, BytesMut::from(&bytes[..])).unwrap();
+    assert_eq!(dynamic.get("type"), Some(FrcValue::Int(3)));
+    assert_eq!(dynamic.get("x"), Some(FrcValue::Float(1.5)));
+    assert_eq!(dynamic.get("ys[1]"), Some(FrcValue::Float(-2.0)));
+    assert_eq!(dynamic.variant("mode"), Some("Enabled"));
+}
+
+#[test]
+fn test_derive_arrays() {
+    use crate as frc_values;
+
+    const LEN: usize = 3;
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    struct ArrayStruct {
+        matrix: [[f64; 3]; 3],
+        values: [i16; LEN],
+        meters: [Meter; LEN - 1],
+        grid: [[Meter; 2]; 2],
+    }
+    assert_eq!(
+        <ArrayStruct as FrcStructure>::SCHEMA,
+        "float64 matrix[9]; int16 values[3]; Meter meters[2]; Meter grid[4]"
+    );
+    assert_eq!(<ArrayStruct as FrcStructure>::SIZE, 72 + 6 + 16 + 32);
+    let meter = |value| Meter { value };
+    let original = ArrayStruct {
+        matrix: [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]],
+        values: [-1, 0, 1],
+        meters: [meter(1.0), meter(2.0)],
+        grid: [[meter(3.0), meter(4.0)], [meter(5.0), meter(6.0)]],
+    };
+    let value = FrcValue::from_struct(original);
+    assert_eq!(value.as_struct::<ArrayStruct>().unwrap(), original);
+    let FrcValue::Struct(desc, bytes) = value else {
+        panic!("not a struct");
+    };
+    let dynamic = structure::DynamicStructure::try_new(desc, BytesMut::from(&bytes[..])).unwrap();
+    assert_eq!(dynamic.get("matrix[5]"), Some(FrcValue::Double(6.0)));
+    assert_eq!(dynamic.get("grid[2].value"), Some(FrcValue::Double(5.0)));
+
+    #[derive(Debug, PartialEq, Clone, Copy, frc_values_macros::FrcStructure)]
+    struct Samples<const N: usize> {
+        samples: [f32; N],
+    }
+    assert_eq!(<Samples<4> as FrcStructure>::TYPE, "Samples_4");
+    assert_eq!(<Samples<4> as FrcStructure>::SCHEMA, "float32 samples[4]");
+    assert_eq!(<Samples<12> as FrcStructure>::SCHEMA, "float32 samples[12]");
+    assert_eq!(<Samples<4> as FrcStructure>::SIZE, 16);
+    let samples = Samples {
+        samples: [0.5, 1.5, 2.5, 3.5],
+    };
+    assert_eq!(
+        FrcValue::from_struct(samples)
+            .as_struct::<Samples<4>>()
+            .unwrap(),
+        samples
+    );
+}
+
+#[test]
+fn test_loggable() {
+    use crate as frc_values;
+    use crate::FrcLoggable;
+
+    #[derive(Debug, PartialEq, Clone, Default, frc_values_macros::FrcLoggable)]
+    struct GyroInputs {
+        yaw: f64,
+        rate: f32,
+    }
+    #[derive(Debug, PartialEq, Clone, frc_values_macros::FrcLoggable)]
+    struct DriveInputs {
+        position: f64,
+        velocities: Vec<f64>,
+        name: String,
+        connected: bool,
+        #[frc_log(structure, rename = "Distance")]
+        distance: Meter,
+        #[frc_log(skip)]
+        cached: u32,
+        #[frc_log(nested)]
+        gyro: GyroInputs,
+    }
+    let inputs = DriveInputs {
+        position: 1.5,
+        velocities: vec![0.5, -0.5],
+        name: "drive".to_owned(),
+        connected: true,
+        distance: Meter { value: 2.0 },
+        cached: 7,
+        gyro: GyroInputs {
+            yaw: 90.0,
+            rate: 0.25,
+        },
+    };
+    assert_eq!(
+        inputs.to_frc_values(),
+        vec![
+            ("position".to_owned(), FrcValue::Double(1.5)),
+            ("velocities".to_owned(), FrcValue::from(vec![0.5, -0.5])),
+            ("name".to_owned(), FrcValue::from("drive")),
+            ("connected".to_owned(), FrcValue::Boolean(true)),
+            ("Distance".to_owned(), FrcValue::from_struct(Meter { value: 2.0 })),
+            ("gyro/yaw".to_owned(), FrcValue::Double(90.0)),
+            ("gyro/rate".to_owned(), FrcValue::Float(0.25)),
+        ]
+    );
+    let mut nested = Vec::new();
+    inputs.gyro.log_into("Drive/Gyro", &mut nested);
+    assert_eq!(nested[0].0, "Drive/Gyro/yaw");
+
+    let mut instant = inputs.to_instant(5);
+    assert_eq!(instant.get("gyro/rate").unwrap().timestamp, 5);
+    assert_eq!(
+        DriveInputs::from_instant(&instant).unwrap(),
+        DriveInputs { cached: 0, ..inputs }
+    );
+
+    instant.set_field("position", FrcValue::from("not a number").to_timestamped(5));
+    assert!(DriveInputs::from_instant(&instant).is_err());
+    instant.remove_field("gyro/yaw");
+    assert!(matches!(
+        GyroInputs::from_logged("gyro", &|path| instant.get(path).map(|value| value.value.clone())),
+        Err(FrcValueError::MissingField(path)) if path == "gyro/yaw"
+    ));
+
+    #[derive(Debug, PartialEq, Clone, frc_values_macros::FrcLoggable)]
+    struct TupleInputs(i64, #[frc_log(nested)] GyroInputs);
+    let tuple = TupleInputs(3, GyroInputs::default());
+    let values = tuple.to_frc_values();
+    assert_eq!(values[0], ("_0".to_owned(), FrcValue::Int(3)));
+    assert_eq!(values[1].0, "_1/yaw");
+    assert_eq!(TupleInputs::from_instant(&tuple.to_instant(0)).unwrap(), tuple);
+}
+
+#[cfg(feature = "protobuf")]
+#[test]
+fn test_protobuf() {
+    use crate::proto::FrcProtoDescDB;
+    use protobuf::{
+        descriptor::{
+            field_descriptor_proto::{Label, Type},
+            DescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+        },
+        reflect::ReflectValueBox,
+        Message,
+    };
+
+    fn field(name: &str, number: i32, r#type: Type, label: Label) -> FieldDescriptorProto {
+        let mut field = FieldDescriptorProto::new();
+        field.set_name(name.to_owned());
+        field.set_number(number);
+        field.set_type(r#type);
+        field.set_label(label);
+        field
+    }
+
+    let mut translation = DescriptorProto::new();
+    translation.set_name("Translation".to_owned());
+    translation.field.push(field("x", 1, Type::TYPE_DOUBLE, Label::LABEL_OPTIONAL));
+    translation.field.push(field("y", 2, Type::TYPE_DOUBLE, Label::LABEL_OPTIONAL));
+
+    let mut pose = DescriptorProto::new();
+    pose.set_name("Pose".to_owned());
+    let mut trans = field("trans", 1, Type::TYPE_MESSAGE, Label::LABEL_OPTIONAL);
+    trans.set_type_name(".frc.test.Translation".to_owned());
+    pose.field.push(trans);
+    pose.field.push(field("ids", 2, Type::TYPE_INT32, Label::LABEL_REPEATED));
+    pose.field.push(field("name", 3, Type::TYPE_STRING, Label::LABEL_OPTIONAL));
+
+    let mut node = DescriptorProto::new();
+    node.set_name("Node".to_owned());
+    let mut child = field("child", 1, Type::TYPE_MESSAGE, Label::LABEL_OPTIONAL);
+    child.set_type_name(".frc.test.Node".to_owned());
+    node.field.push(child);
+    node.field.push(field("value", 2, Type::TYPE_INT32, Label::LABEL_OPTIONAL));
+
+    let mut file = FileDescriptorProto::new();
+    file.set_name("frc_test.proto".to_owned());
+    file.set_package("frc.test".to_owned());
+    file.set_syntax("proto3".to_owned());
+    file.message_type.push(translation);
+    file.message_type.push(pose);
+    file.message_type.push(node);
+
+    let mut dependent = FileDescriptorProto::new();
+    dependent.set_name("frc_dependent.proto".to_owned());
+    dependent.dependency.push("frc_unregistered.proto".to_owned());
+    assert!(matches!(
+        FrcProtoDescDB::add_file(dependent),
+        Err(FrcValueError::Protobuf(_))
+    ));
+
+    FrcProtoDescDB::add_file_bytes(&file.write_to_bytes().unwrap()).unwrap();
+    assert!(FrcProtoDescDB::contains("frc.test.Translation"));
+    let desc = FrcProtoDescDB::get("frc.test.Pose").unwrap();
+
+    let translation_desc = FrcProtoDescDB::get("frc.test.Translation").unwrap();
+    let mut trans = translation_desc.descriptor.new_instance();
+    let x = translation_desc.descriptor.field_by_name("x").unwrap();
+    x.set_singular_field(&mut *trans, ReflectValueBox::F64(1.5));
+    let mut message = desc.descriptor.new_instance();
+    let fields = &desc.descriptor;
+    fields
+        .field_by_name("trans")
+        .unwrap()
+        .set_singular_field(&mut *message, ReflectValueBox::Message(trans));
+    let ids = fields.field_by_name("ids").unwrap();
+    ids.mut_repeated(&mut *message).push(ReflectValueBox::I32(3));
+    ids.mut_repeated(&mut *message).push(ReflectValueBox::I32(-4));
+    fields
+        .field_by_name("name")
+        .unwrap()
+        .set_singular_field(&mut *message, ReflectValueBox::String("robot".to_owned()));
+
+    let value = FrcValue::Protobuf(
+        desc,
+        Box::new(message.write_to_bytes_dyn().unwrap().into()),
+    );
+    assert_eq!(value.get_type(), FrcType::Protobuf);
+    assert_eq!(
+        value.decode_protobuf().unwrap(),
+        vec![
+            ("trans.x".to_owned(), FrcValue::Double(1.5)),
+            ("trans.y".to_owned(), FrcValue::Double(0.0)),
+            ("ids".to_owned(), FrcValue::IntArray(vec![3, -4])),
+            ("name".to_owned(), FrcValue::String("robot".to_owned())),
+        ]
+    );
+    assert!(FrcValue::Int(1).decode_protobuf().is_err());
+
+    // unset messages aren't expanded, even if their type contains itself
+    let node_desc = FrcProtoDescDB::get("frc.test.Node").unwrap();
+    assert_eq!(
+        node_desc.decode(&[]).unwrap(),
+        vec![("value".to_owned(), FrcValue::Int(0))]
+    );
+    assert_eq!(
+        node_desc.decode(&[0x0a, 0x02, 0x10, 0x07]).unwrap(),
+        vec![
+            ("child.value".to_owned(), FrcValue::Int(7)),
+            ("value".to_owned(), FrcValue::Int(0)),
+        ]
+    );
+
+    let mut buffer = FrcValueBuffer::new(FrcType::Protobuf);
+    buffer.push(&value).unwrap();
+    assert_eq!(buffer.proto_desc(), Some(desc));
+    assert_eq!(buffer.get_value(0), Some(value));
+
+    let mut double = protobuf::well_known_types::wrappers::DoubleValue::new();
+    double.value = 2.5;
+    let value = FrcValue::from_message(&double).unwrap();
+    assert!(FrcProtoDescDB::contains("google.protobuf.DoubleValue"));
+    assert_eq!(
+        value.decode_protobuf().unwrap(),
+        vec![("value".to_owned(), FrcValue::Double(2.5))]
+    );
+    assert_eq!(value.try_into_message::<protobuf::well_known_types::wrappers::DoubleValue>().unwrap(), double);
+}
@@ -0,0 +1,211 @@
+use std::ops::{Bound, RangeBounds};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{FrcTimestamp, FrcTimestampedValue, FrcType};
+
+/// Which neighbour to return when a timeline lookup misses an exact timestamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrcTimelineLookup {
+    /// The latest value at or before the timestamp
+    Before,
+    /// The earliest value at or after the timestamp
+    After,
+    /// Whichever value is closest to the timestamp, ties go to the earlier value
+    Nearest,
+}
+
+/// A series of timestamped values that is always sorted by timestamp
+///
+/// Values with equal timestamps keep their insertion order
+#[derive(Debug, Clone, Default, PartialEq, Hash, Serialize)]
+pub struct FrcTimeline(Vec<FrcTimestampedValue>);
+
+impl<'a> Deserialize<'a> for FrcTimeline {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as serde::Deserializer<'a>>::Error>
+    where
+        D: serde::Deserializer<'a>,
+    {
+        Vec::<FrcTimestampedValue>::deserialize(deserializer).map(Self::from_vec)
+    }
+}
+
+impl IntoIterator for FrcTimeline {
+    type Item = FrcTimestampedValue;
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a FrcTimeline {
+    type Item = &'a FrcTimestampedValue;
+    type IntoIter = std::slice::Iter<'a, FrcTimestampedValue>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl FromIterator<FrcTimestampedValue> for FrcTimeline {
+    fn from_iter<I: IntoIterator<Item = FrcTimestampedValue>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+    }
+}
+
+impl From<Vec<FrcTimestampedValue>> for FrcTimeline {
+    fn from(vec: Vec<FrcTimestampedValue>) -> Self {
+        Self::from_vec(vec)
+    }
+}
+
+impl FrcTimeline {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+    /// The caller guarantees the vec is already sorted by timestamp
+    pub fn from_vec_sorted(vec: Vec<FrcTimestampedValue>) -> Self {
+        debug_assert!(vec.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+        Self(vec)
+    }
+    pub fn from_vec(mut vec: Vec<FrcTimestampedValue>) -> Self {
+        vec.sort_by_key(|v| v.timestamp);
+        Self(vec)
+    }
+    pub fn to_vec(self) -> Vec<FrcTimestampedValue> {
+        self.0
+    }
+    pub fn as_slice(&self) -> &[FrcTimestampedValue] {
+        &self.0
+    }
+    pub fn iter(&self) -> std::slice::Iter<'_, FrcTimestampedValue> {
+        self.0.iter()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn first(&self) -> Option<&FrcTimestampedValue> {
+        self.0.first()
+    }
+    pub fn last(&self) -> Option<&FrcTimestampedValue> {
+        self.0.last()
+    }
+
+    /// Inserts the value after any values with a lower or equal timestamp,
+    /// returns the index it was inserted at
+    pub fn insert(&mut self, value: FrcTimestampedValue) -> usize {
+        let index = self.0.partition_point(|v| v.timestamp <= value.timestamp);
+        self.0.insert(index, value);
+        index
+    }
+
+    /// Returns the type shared by every value, `None` if empty or mixed
+    pub fn get_type(&self) -> Option<FrcType> {
+        let first_type = self.0.first()?.get_type();
+        if self.is_all_same_type_as(&first_type) {
+            Some(first_type)
+        } else {
+            None
+        }
+    }
+    pub fn is_all_same_type(&self) -> bool {
+        match self.0.first() {
+            Some(first) => self.is_all_same_type_as(&first.get_type()),
+            None => true,
+        }
+    }
+    pub fn is_all_same_type_as(&self, other: &FrcType) -> bool {
+        self.0.iter().all(|v| v.get_type() == *other)
+    }
+
+    /// Finds the value at the given timestamp, falling back to a neighbour chosen by `lookup`
+    /// if there is no exact match.
+    ///
+    /// With duplicate timestamps `Before` returns the last of them and `After` the first.
+    pub fn get_by_timestamp(
+        &self,
+        timestamp: FrcTimestamp,
+        lookup: FrcTimelineLookup,
+    ) -> Option<&FrcTimestampedValue> {
+        self.index_by_timestamp(timestamp, lookup)
+            .map(|index| &self.0[index])
+    }
+
+    /// Same as [`FrcTimeline::get_by_timestamp`] but returns the index into the timeline
+    pub fn index_by_timestamp(
+        &self,
+        timestamp: FrcTimestamp,
+        lookup: FrcTimelineLookup,
+    ) -> Option<usize> {
+        let after = self.0.partition_point(|v| v.timestamp < timestamp);
+        let before = self.0.partition_point(|v| v.timestamp <= timestamp);
+        let before = before.checked_sub(1);
+        let after = Some(after).filter(|i| *i < self.0.len());
+        match lookup {
+            FrcTimelineLookup::Before => before,
+            FrcTimelineLookup::After => after,
+            FrcTimelineLookup::Nearest => match (before, after) {
+                (Some(b), Some(a)) => {
+                    if timestamp - self.0[b].timestamp <= self.0[a].timestamp - timestamp {
+                        Some(b)
+                    } else {
+                        Some(a)
+                    }
+                }
+                (b, a) => b.or(a),
+            },
+        }
+    }
+
+    /// Returns all values with a timestamp inside of the range
+    pub fn range(&self, range: impl RangeBounds<FrcTimestamp>) -> &[FrcTimestampedValue] {
+        let start = match range.start_bound() {
+            Bound::Included(ts) => self.0.partition_point(|v| v.timestamp < *ts),
+            Bound::Excluded(ts) => self.0.partition_point(|v| v.timestamp <= *ts),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(ts) => self.0.partition_point(|v| v.timestamp <= *ts),
+            Bound::Excluded(ts) => self.0.partition_point(|v| v.timestamp < *ts),
+            Bound::Unbounded => self.0.len(),
+        };
+        if start >= end {
+            return &[];
+        }
+        &self.0[start..end]
+    }
+
+    /// Merges another timeline into this one,
+    /// on equal timestamps values from `self` come before values from `other`
+    pub fn merge(&mut self, other: FrcTimeline) {
+        if other.is_empty() {
+            return;
+        }
+        if self
+            .last()
+            .is_none_or(|v| v.timestamp <= other.0[0].timestamp)
+        {
+            self.0.extend(other.0);
+            return;
+        }
+        let mut merged = Vec::with_capacity(self.0.len() + other.0.len());
+        let mut left = std::mem::take(&mut self.0).into_iter().peekable();
+        let mut right = other.0.into_iter().peekable();
+        loop {
+            let take_left = match (left.peek(), right.peek()) {
+                (Some(l), Some(r)) => l.timestamp <= r.timestamp,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+            if take_left {
+                merged.extend(left.next());
+            } else {
+                merged.extend(right.next());
+            }
+        }
+        self.0 = merged;
+    }
+}
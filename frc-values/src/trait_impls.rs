@@ -117,10 +117,10 @@ impl Into<FrcValue> for FrcTimestampedValue {
     }
 }
 
-impl TryFrom<FrcValue> for f64 {
+impl TryFrom<&FrcValue> for f64 {
     type Error = crate::FrcValueError;
-    fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
-        match value {
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match *value {
             FrcValue::Double(v) => Ok(v),
             FrcValue::Float(v) => Ok(v as f64),
             _ => Err(FrcValueError::InvalidCast(
@@ -132,10 +132,17 @@ impl TryFrom<FrcValue> for f64 {
     }
 }
 
-impl TryFrom<FrcValue> for f32 {
+impl TryFrom<FrcValue> for f64 {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
-        match value {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for f32 {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match *value {
             FrcValue::Double(v) => {
                 if v > f32::MAX as f64 {
                     Err(FrcValueError::InvalidCast(
@@ -163,10 +170,17 @@ impl TryFrom<FrcValue> for f32 {
     }
 }
 
-impl TryFrom<FrcValue> for i64 {
+impl TryFrom<FrcValue> for f32 {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
-        match value {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for i64 {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match *value {
             FrcValue::Int(v) => Ok(v),
             _ => Err(FrcValueError::InvalidCast(
                 value.get_type(),
@@ -177,10 +191,17 @@ impl TryFrom<FrcValue> for i64 {
     }
 }
 
-impl TryFrom<FrcValue> for i32 {
+impl TryFrom<FrcValue> for i64 {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
-        match value {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for i32 {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match *value {
             FrcValue::Int(v) => {
                 if v > i32::MAX as i64 {
                     Err(FrcValueError::InvalidCast(
@@ -207,10 +228,17 @@ impl TryFrom<FrcValue> for i32 {
     }
 }
 
-impl TryFrom<FrcValue> for i16 {
+impl TryFrom<FrcValue> for i32 {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
-        match value {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for i16 {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match *value {
             FrcValue::Int(v) => {
                 if v > i16::MAX as i64 {
                     Err(FrcValueError::InvalidCast(
@@ -237,10 +265,17 @@ impl TryFrom<FrcValue> for i16 {
     }
 }
 
-impl TryFrom<FrcValue> for i8 {
+impl TryFrom<FrcValue> for i16 {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
-        match value {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for i8 {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match *value {
             FrcValue::Int(v) => {
                 if v > i8::MAX as i64 {
                     Err(FrcValueError::InvalidCast(
@@ -267,10 +302,17 @@ impl TryFrom<FrcValue> for i8 {
     }
 }
 
-impl TryFrom<FrcValue> for u64 {
+impl TryFrom<FrcValue> for i8 {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
-        match value {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for u64 {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match *value {
             FrcValue::Int(v) => {
                 if v < 0 {
                     Err(FrcValueError::InvalidCast(
@@ -291,10 +333,17 @@ impl TryFrom<FrcValue> for u64 {
     }
 }
 
-impl TryFrom<FrcValue> for u32 {
+impl TryFrom<FrcValue> for u64 {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
-        match value {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for u32 {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match *value {
             FrcValue::Int(v) => {
                 if v < 0 {
                     Err(FrcValueError::InvalidCast(
@@ -321,10 +370,17 @@ impl TryFrom<FrcValue> for u32 {
     }
 }
 
-impl TryFrom<FrcValue> for u16 {
+impl TryFrom<FrcValue> for u32 {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
-        match value {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for u16 {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match *value {
             FrcValue::Int(v) => {
                 if v < 0 {
                     Err(FrcValueError::InvalidCast(
@@ -351,10 +407,17 @@ impl TryFrom<FrcValue> for u16 {
     }
 }
 
-impl TryFrom<FrcValue> for u8 {
+impl TryFrom<FrcValue> for u16 {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
-        match value {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for u8 {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match *value {
             FrcValue::Int(v) => {
                 if v < 0 {
                     Err(FrcValueError::InvalidCast(
@@ -381,10 +444,17 @@ impl TryFrom<FrcValue> for u8 {
     }
 }
 
-impl TryFrom<FrcValue> for bool {
+impl TryFrom<FrcValue> for u8 {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
-        match value {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for bool {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match *value {
             FrcValue::Boolean(v) => Ok(v),
             _ => Err(FrcValueError::InvalidCast(
                 value.get_type(),
@@ -395,6 +465,41 @@ impl TryFrom<FrcValue> for bool {
     }
 }
 
+impl TryFrom<FrcValue> for bool {
+    type Error = crate::FrcValueError;
+    fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for String {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match value {
+            FrcValue::String(v) => Ok(v.clone()),
+            _ => Err(FrcValueError::InvalidCast(
+                value.get_type(),
+                stringify!(String),
+                CastErrorReason::Type
+            )),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a FrcValue> for &'a str {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &'a FrcValue) -> Result<Self, Self::Error> {
+        match value {
+            FrcValue::String(v) => Ok(v),
+            _ => Err(FrcValueError::InvalidCast(
+                value.get_type(),
+                stringify!(&str),
+                CastErrorReason::Type
+            )),
+        }
+    }
+}
+
 impl TryFrom<FrcValue> for String {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
@@ -409,6 +514,23 @@ impl TryFrom<FrcValue> for String {
     }
 }
 
+impl TryFrom<&FrcValue> for Vec<f64> {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match value {
+            FrcValue::DoubleArray(va) => Ok(va.clone()),
+            FrcValue::FloatArray(va) => Ok(
+                va.iter().map(|v| *v as f64).collect()
+            ),
+            _ => Err(FrcValueError::InvalidCast(
+                value.get_type(),
+                stringify!(Vec<f64>),
+                CastErrorReason::Type
+            )),
+        }
+    }
+}
+
 impl TryFrom<FrcValue> for Vec<f64> {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
@@ -426,11 +548,25 @@ impl TryFrom<FrcValue> for Vec<f64> {
     }
 }
 
-impl TryFrom<FrcValue> for Vec<f32> {
+impl<'a> TryFrom<&'a FrcValue> for &'a [f64] {
     type Error = crate::FrcValueError;
-    fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
+    fn try_from(value: &'a FrcValue) -> Result<Self, Self::Error> {
         match value {
-            FrcValue::DoubleArray(ref va) => {
+            FrcValue::DoubleArray(v) => Ok(v),
+            _ => Err(FrcValueError::InvalidCast(
+                value.get_type(),
+                stringify!(&[f64]),
+                CastErrorReason::Type
+            )),
+        }
+    }
+}
+
+impl TryFrom<&FrcValue> for Vec<f32> {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match value {
+            FrcValue::DoubleArray(va) => {
                 let mut ret_vec = Vec::with_capacity(va.len());
                 for v in va {
                     if *v > f32::MAX as f64 {
@@ -451,7 +587,7 @@ impl TryFrom<FrcValue> for Vec<f32> {
                 }
                 Ok(ret_vec)
             }
-            FrcValue::FloatArray(v) => Ok(v),
+            FrcValue::FloatArray(v) => Ok(v.clone()),
             _ => Err(FrcValueError::InvalidCast(
                 value.get_type(),
                 stringify!(Vec<f32>),
@@ -461,6 +597,44 @@ impl TryFrom<FrcValue> for Vec<f32> {
     }
 }
 
+impl TryFrom<FrcValue> for Vec<f32> {
+    type Error = crate::FrcValueError;
+    fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
+        match value {
+            FrcValue::FloatArray(v) => Ok(v),
+            _ => Self::try_from(&value),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a FrcValue> for &'a [f32] {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &'a FrcValue) -> Result<Self, Self::Error> {
+        match value {
+            FrcValue::FloatArray(v) => Ok(v),
+            _ => Err(FrcValueError::InvalidCast(
+                value.get_type(),
+                stringify!(&[f32]),
+                CastErrorReason::Type
+            )),
+        }
+    }
+}
+
+impl TryFrom<&FrcValue> for Vec<i64> {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match value {
+            FrcValue::IntArray(v) => Ok(v.clone()),
+            _ => Err(FrcValueError::InvalidCast(
+                value.get_type(),
+                stringify!(Vec<i64>),
+                CastErrorReason::Type
+            )),
+        }
+    }
+}
+
 impl TryFrom<FrcValue> for Vec<i64> {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
@@ -475,11 +649,25 @@ impl TryFrom<FrcValue> for Vec<i64> {
     }
 }
 
-impl TryFrom<FrcValue> for Vec<i32> {
+impl<'a> TryFrom<&'a FrcValue> for &'a [i64] {
     type Error = crate::FrcValueError;
-    fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
+    fn try_from(value: &'a FrcValue) -> Result<Self, Self::Error> {
         match value {
-            FrcValue::IntArray(ref va) => {
+            FrcValue::IntArray(v) => Ok(v),
+            _ => Err(FrcValueError::InvalidCast(
+                value.get_type(),
+                stringify!(&[i64]),
+                CastErrorReason::Type
+            )),
+        }
+    }
+}
+
+impl TryFrom<&FrcValue> for Vec<i32> {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match value {
+            FrcValue::IntArray(va) => {
                 let mut ret_vec = Vec::with_capacity(va.len());
                 for v in va {
                     if *v > i32::MAX as i64 {
@@ -509,11 +697,18 @@ impl TryFrom<FrcValue> for Vec<i32> {
     }
 }
 
-impl TryFrom<FrcValue> for Vec<i16> {
+impl TryFrom<FrcValue> for Vec<i32> {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for Vec<i16> {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
         match value {
-            FrcValue::IntArray(ref va) => {
+            FrcValue::IntArray(va) => {
                 let mut ret_vec = Vec::with_capacity(va.len());
                 for v in va {
                     if *v > i16::MAX as i64 {
@@ -543,11 +738,18 @@ impl TryFrom<FrcValue> for Vec<i16> {
     }
 }
 
-impl TryFrom<FrcValue> for Vec<i8> {
+impl TryFrom<FrcValue> for Vec<i16> {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for Vec<i8> {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
         match value {
-            FrcValue::IntArray(ref va) => {
+            FrcValue::IntArray(va) => {
                 let mut ret_vec = Vec::with_capacity(va.len());
                 for v in va {
                     if *v > i8::MAX as i64 {
@@ -577,11 +779,18 @@ impl TryFrom<FrcValue> for Vec<i8> {
     }
 }
 
-impl TryFrom<FrcValue> for Vec<u64> {
+impl TryFrom<FrcValue> for Vec<i8> {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for Vec<u64> {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
         match value {
-            FrcValue::IntArray(ref va) => {
+            FrcValue::IntArray(va) => {
                 let mut ret_vec = Vec::with_capacity(va.len());
                 for v in va {
                     if *v < 0 {
@@ -605,11 +814,18 @@ impl TryFrom<FrcValue> for Vec<u64> {
     }
 }
 
-impl TryFrom<FrcValue> for Vec<u32> {
+impl TryFrom<FrcValue> for Vec<u64> {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for Vec<u32> {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
         match value {
-            FrcValue::IntArray(ref va) => {
+            FrcValue::IntArray(va) => {
                 let mut ret_vec = Vec::with_capacity(va.len());
                 for v in va {
                     if *v < 0 {
@@ -639,11 +855,18 @@ impl TryFrom<FrcValue> for Vec<u32> {
     }
 }
 
-impl TryFrom<FrcValue> for Vec<u16> {
+impl TryFrom<FrcValue> for Vec<u32> {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for Vec<u16> {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
         match value {
-            FrcValue::IntArray(ref va) => {
+            FrcValue::IntArray(va) => {
                 let mut ret_vec = Vec::with_capacity(va.len());
                 for v in va {
                     if *v < 0 {
@@ -673,11 +896,18 @@ impl TryFrom<FrcValue> for Vec<u16> {
     }
 }
 
-impl TryFrom<FrcValue> for Vec<u8> {
+impl TryFrom<FrcValue> for Vec<u16> {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for Vec<u8> {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
         match value {
-            FrcValue::IntArray(ref va) => {
+            FrcValue::IntArray(va) => {
                 let mut ret_vec = Vec::with_capacity(va.len());
                 for v in va {
                     if *v < 0 {
@@ -707,6 +937,27 @@ impl TryFrom<FrcValue> for Vec<u8> {
     }
 }
 
+impl TryFrom<FrcValue> for Vec<u8> {
+    type Error = crate::FrcValueError;
+    fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&FrcValue> for Vec<bool> {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match value {
+            FrcValue::BooleanArray(v) => Ok(v.clone()),
+            _ => Err(FrcValueError::InvalidCast(
+                value.get_type(),
+                stringify!(Vec<bool>),
+                CastErrorReason::Type
+            )),
+        }
+    }
+}
+
 impl TryFrom<FrcValue> for Vec<bool> {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
@@ -721,6 +972,34 @@ impl TryFrom<FrcValue> for Vec<bool> {
     }
 }
 
+impl<'a> TryFrom<&'a FrcValue> for &'a [bool] {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &'a FrcValue) -> Result<Self, Self::Error> {
+        match value {
+            FrcValue::BooleanArray(v) => Ok(v),
+            _ => Err(FrcValueError::InvalidCast(
+                value.get_type(),
+                stringify!(&[bool]),
+                CastErrorReason::Type
+            )),
+        }
+    }
+}
+
+impl TryFrom<&FrcValue> for Vec<String> {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &FrcValue) -> Result<Self, Self::Error> {
+        match value {
+            FrcValue::StringArray(v) => Ok(v.clone()),
+            _ => Err(FrcValueError::InvalidCast(
+                value.get_type(),
+                stringify!(Vec<String>),
+                CastErrorReason::Type
+            )),
+        }
+    }
+}
+
 impl TryFrom<FrcValue> for Vec<String> {
     type Error = crate::FrcValueError;
     fn try_from(value: FrcValue) -> Result<Self, Self::Error> {
@@ -735,9 +1014,33 @@ impl TryFrom<FrcValue> for Vec<String> {
     }
 }
 
+impl<'a> TryFrom<&'a FrcValue> for &'a [String] {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &'a FrcValue) -> Result<Self, Self::Error> {
+        match value {
+            FrcValue::StringArray(v) => Ok(v),
+            _ => Err(FrcValueError::InvalidCast(
+                value.get_type(),
+                stringify!(&[String]),
+                CastErrorReason::Type
+            )),
+        }
+    }
+}
 
-
-
+impl<'a> TryFrom<&'a FrcValue> for &'a bytes::Bytes {
+    type Error = crate::FrcValueError;
+    fn try_from(value: &'a FrcValue) -> Result<Self, Self::Error> {
+        match value {
+            FrcValue::Raw(v) => Ok(v.as_ref()),
+            _ => Err(FrcValueError::InvalidCast(
+                value.get_type(),
+                stringify!(&bytes::Bytes),
+                CastErrorReason::Type
+            )),
+        }
+    }
+}
 
 #[cfg(any(feature = "rmpv-casting", feature = "json-casting"))]
 use crate::FrcType;
@@ -803,37 +1106,23 @@ impl From<FrcValue> for MPValue {
             FrcValue::Double(f) => Self::F64(f),
             FrcValue::String(s) => Self::String(s.into()),
             FrcValue::Raw(b) => Self::Binary(b.to_vec()),
-            FrcValue::BooleanArray(a) => Self::Array(
-                a.into_iter()
-                    .map(|v| Self::Boolean(v))
-                    .collect::<Vec<Self>>()
-                    .into(),
-            ),
+            FrcValue::BooleanArray(a) => Self::Array(a.into_iter().map(Self::Boolean).collect()),
             FrcValue::IntArray(a) => Self::Array(
                 a.into_iter()
                     .map(|v| Self::Integer(v.into()))
-                    .collect::<Vec<Self>>()
-                    .into(),
-            ),
-            FrcValue::FloatArray(a) => Self::Array(
-                a.into_iter()
-                    .map(|v| Self::F32(v))
-                    .collect::<Vec<Self>>()
-                    .into(),
-            ),
-            FrcValue::DoubleArray(a) => Self::Array(
-                a.into_iter()
-                    .map(|v| Self::F64(v))
-                    .collect::<Vec<Self>>()
-                    .into(),
+                    .collect(),
             ),
+            FrcValue::FloatArray(a) => Self::Array(a.into_iter().map(Self::F32).collect()),
+            FrcValue::DoubleArray(a) => Self::Array(a.into_iter().map(Self::F64).collect()),
             FrcValue::StringArray(a) => Self::Array(
                 a.into_iter()
                     .map(|v| Self::String(v.into()))
-                    .collect::<Vec<Self>>()
-                    .into(),
+                    .collect(),
             ),
             FrcValue::Struct(_, b) => Self::Binary(b.to_vec()),
+            FrcValue::StructArray(_, b) => Self::Binary(b.to_vec()),
+            #[cfg(feature = "protobuf")]
+            FrcValue::Protobuf(_, b) => Self::Binary(b.to_vec()),
         }
     }
 }
@@ -913,7 +1202,7 @@ impl From<FrcValue> for JSONValue {
             }),
             FrcValue::Float(f) => Self::Number(serde_json::Number::from_f64(f as f64).unwrap()),
             FrcValue::Double(f) => Self::Number(serde_json::Number::from_f64(f).unwrap()),
-            FrcValue::String(s) => Self::String(s.into()),
+            FrcValue::String(s) => Self::String(s),
             FrcValue::Raw(b) => Self::Array(
                 b.iter()
                     .map(|v| Self::Number((*v as i64).into()))
@@ -921,7 +1210,7 @@ impl From<FrcValue> for JSONValue {
             ),
             FrcValue::BooleanArray(a) => Self::Array(
                 a.into_iter()
-                    .map(|v| Self::Bool(v))
+                    .map(Self::Bool)
                     .collect::<Vec<Self>>()
             ),
             FrcValue::IntArray(a) => Self::Array(
@@ -947,10 +1236,27 @@ impl From<FrcValue> for JSONValue {
             ),
             FrcValue::StringArray(a) => Self::Array(
                 a.into_iter()
-                    .map(|v| Self::String(v.into()))
+                    .map(Self::String)
                     .collect::<Vec<Self>>()
             ),
-            FrcValue::Struct(_, b) => Self::Array(
+            // structs with an invalid schema fall back to their bytes
+            FrcValue::Struct(desc, b) => crate::structure::json::struct_json(&desc, &b)
+                .unwrap_or_else(|_| Self::Array(
+                    b.iter()
+                        .map(|v| Self::Number((*v as u64).into()))
+                        .collect::<Vec<Self>>()
+                )),
+            FrcValue::StructArray(desc, b) => b.chunks(desc.size().max(1))
+                .map(|element| crate::structure::json::struct_json(&desc, element))
+                .collect::<Result<Vec<Self>, _>>()
+                .map(Self::Array)
+                .unwrap_or_else(|_| Self::Array(
+                    b.iter()
+                        .map(|v| Self::Number((*v as u64).into()))
+                        .collect::<Vec<Self>>()
+                )),
+            #[cfg(feature = "protobuf")]
+            FrcValue::Protobuf(_, b) => Self::Array(
                 b.iter()
                     .map(|v| Self::Number((*v as u64).into()))
                     .collect::<Vec<Self>>()
@@ -1,4 +1,4 @@
-use crate::FrcValue;
+use crate::{FrcTableInstant, FrcTimestamp, FrcValue, FrcValueError};
 
 
 
@@ -10,4 +10,41 @@ impl<T: Into<FrcValue>> IntoFrcValue for T {
     fn into_frc_value(self) -> FrcValue {
         self.into()
     }
-}
\ No newline at end of file
+}
+
+/// A type logged as a group of topics, one for each field (like AdvantageKit's `@AutoLog`),
+/// usually derived with `frc_values_macros::FrcLoggable`
+pub trait FrcLoggable: Sized {
+    /// Appends the `(path, value)` of every field, paths are `/` separated under `prefix`
+    fn log_into(&self, prefix: &str, values: &mut Vec<(String, FrcValue)>);
+
+    /// Rebuilds the value from the fields under `prefix`, `get` looks up the value of a full path
+    fn from_logged(
+        prefix: &str,
+        get: &dyn Fn(&str) -> Option<FrcValue>,
+    ) -> Result<Self, FrcValueError>;
+
+    /// The `(path, value)` of every field, nested loggables are prefixed with their field name
+    fn to_frc_values(&self) -> Vec<(String, FrcValue)> {
+        let mut values = Vec::new();
+        self.log_into("", &mut values);
+        values
+    }
+
+    /// Every field as an entry of an instant at `timestamp`
+    fn to_instant(&self, timestamp: FrcTimestamp) -> FrcTableInstant {
+        FrcTableInstant::from_tuples(
+            self.to_frc_values()
+                .into_iter()
+                .map(|(path, value)| (path, value.to_timestamped(timestamp)))
+                .collect(),
+        )
+    }
+
+    /// Rebuilds the value from the entries of an instant, for replaying a log
+    fn from_instant(instant: &FrcTableInstant) -> Result<Self, FrcValueError> {
+        Self::from_logged("", &|path| {
+            instant.get(path).map(|value| value.value.clone())
+        })
+    }
+}
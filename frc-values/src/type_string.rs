@@ -0,0 +1,193 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{FrcType, FrcValue, FrcValueError};
+
+/// A network tables / data log type string
+///
+/// Unlike [`FrcType`] this keeps the information needed to write the value back out,
+/// `json` isn't collapsed into `string`, `msgpack`, `rpc` and `structschema` aren't collapsed into `raw`
+/// and struct and protobuf types keep their names.
+/// Parsing and formatting round-trip exactly
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FrcTypeString {
+    Boolean,
+    Int,
+    Float,
+    Double,
+    String,
+    Json,
+    BooleanArray,
+    IntArray,
+    FloatArray,
+    DoubleArray,
+    StringArray,
+    Raw,
+    Rpc,
+    MsgPack,
+    StructSchema,
+    /// `struct:Name`
+    Struct(String),
+    /// `struct:Name[]`
+    StructArray(String),
+    /// `proto:Name`
+    Proto(String),
+}
+
+impl FrcTypeString {
+    /// The [`FrcType`] values of this type are stored as
+    pub fn frc_type(&self) -> FrcType {
+        match self {
+            FrcTypeString::Boolean => FrcType::Boolean,
+            FrcTypeString::Int => FrcType::Int,
+            FrcTypeString::Float => FrcType::Float,
+            FrcTypeString::Double => FrcType::Double,
+            FrcTypeString::String | FrcTypeString::Json => FrcType::String,
+            FrcTypeString::BooleanArray => FrcType::BoolArray,
+            FrcTypeString::IntArray => FrcType::IntArray,
+            FrcTypeString::FloatArray => FrcType::FloatArray,
+            FrcTypeString::DoubleArray => FrcType::DoubleArray,
+            FrcTypeString::StringArray => FrcType::StringArray,
+            FrcTypeString::Raw
+            | FrcTypeString::Rpc
+            | FrcTypeString::MsgPack
+            | FrcTypeString::StructSchema => FrcType::Raw,
+            FrcTypeString::Struct(_) => FrcType::Struct,
+            FrcTypeString::StructArray(_) => FrcType::StructArray,
+            #[cfg(feature = "protobuf")]
+            FrcTypeString::Proto(_) => FrcType::Protobuf,
+            #[cfg(not(feature = "protobuf"))]
+            FrcTypeString::Proto(_) => FrcType::Raw,
+        }
+    }
+
+    /// The struct or protobuf type name, `None` for every other type
+    pub fn type_name(&self) -> Option<&str> {
+        match self {
+            FrcTypeString::Struct(name)
+            | FrcTypeString::StructArray(name)
+            | FrcTypeString::Proto(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+impl Display for FrcTypeString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrcTypeString::Boolean => write!(f, "boolean"),
+            FrcTypeString::Int => write!(f, "int"),
+            FrcTypeString::Float => write!(f, "float"),
+            FrcTypeString::Double => write!(f, "double"),
+            FrcTypeString::String => write!(f, "string"),
+            FrcTypeString::Json => write!(f, "json"),
+            FrcTypeString::BooleanArray => write!(f, "boolean[]"),
+            FrcTypeString::IntArray => write!(f, "int[]"),
+            FrcTypeString::FloatArray => write!(f, "float[]"),
+            FrcTypeString::DoubleArray => write!(f, "double[]"),
+            FrcTypeString::StringArray => write!(f, "string[]"),
+            FrcTypeString::Raw => write!(f, "raw"),
+            FrcTypeString::Rpc => write!(f, "rpc"),
+            FrcTypeString::MsgPack => write!(f, "msgpack"),
+            FrcTypeString::StructSchema => write!(f, "structschema"),
+            FrcTypeString::Struct(name) => write!(f, "struct:{}", name),
+            FrcTypeString::StructArray(name) => write!(f, "struct:{}[]", name),
+            FrcTypeString::Proto(name) => write!(f, "proto:{}", name),
+        }
+    }
+}
+
+impl FromStr for FrcTypeString {
+    type Err = FrcValueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let type_string = match s {
+            "boolean" => FrcTypeString::Boolean,
+            "int" => FrcTypeString::Int,
+            "float" => FrcTypeString::Float,
+            "double" => FrcTypeString::Double,
+            "string" => FrcTypeString::String,
+            "json" => FrcTypeString::Json,
+            "boolean[]" => FrcTypeString::BooleanArray,
+            "int[]" => FrcTypeString::IntArray,
+            "float[]" => FrcTypeString::FloatArray,
+            "double[]" => FrcTypeString::DoubleArray,
+            "string[]" => FrcTypeString::StringArray,
+            "raw" => FrcTypeString::Raw,
+            "rpc" => FrcTypeString::Rpc,
+            "msgpack" => FrcTypeString::MsgPack,
+            "structschema" => FrcTypeString::StructSchema,
+            _ => {
+                if let Some(name) = s.strip_prefix("struct:") {
+                    match name.strip_suffix("[]") {
+                        Some(name) => FrcTypeString::StructArray(valid_name(s, name)?),
+                        None => FrcTypeString::Struct(valid_name(s, name)?),
+                    }
+                } else if let Some(name) = s.strip_prefix("proto:") {
+                    FrcTypeString::Proto(valid_name(s, name)?)
+                } else {
+                    return Err(FrcValueError::InvalidTypeString(s.to_owned()));
+                }
+            }
+        };
+        Ok(type_string)
+    }
+}
+
+fn valid_name(s: &str, name: &str) -> Result<String, FrcValueError> {
+    if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == '[' || c == ']') {
+        Err(FrcValueError::InvalidTypeString(s.to_owned()))
+    } else {
+        Ok(name.to_owned())
+    }
+}
+
+impl Serialize for FrcTypeString {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'a> Deserialize<'a> for FrcTypeString {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'a>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl FrcValue {
+    /// The type string this value would be published with, `None` for `Void`
+    ///
+    /// Values don't know if they hold json, msgpack or rpc data
+    /// so these are reported as `string` and `raw`
+    pub fn type_string(&self) -> Option<FrcTypeString> {
+        let type_string = match self {
+            FrcValue::Void => return None,
+            FrcValue::Boolean(_) => FrcTypeString::Boolean,
+            FrcValue::Int(_) => FrcTypeString::Int,
+            FrcValue::Double(_) => FrcTypeString::Double,
+            FrcValue::Float(_) => FrcTypeString::Float,
+            FrcValue::String(_) => FrcTypeString::String,
+            FrcValue::BooleanArray(_) => FrcTypeString::BooleanArray,
+            FrcValue::IntArray(_) => FrcTypeString::IntArray,
+            FrcValue::FloatArray(_) => FrcTypeString::FloatArray,
+            FrcValue::DoubleArray(_) => FrcTypeString::DoubleArray,
+            FrcValue::StringArray(_) => FrcTypeString::StringArray,
+            FrcValue::Raw(_) => FrcTypeString::Raw,
+            FrcValue::Struct(desc, _) => FrcTypeString::Struct(desc.type_str().to_owned()),
+            FrcValue::StructArray(desc, _) => {
+                FrcTypeString::StructArray(desc.type_str().to_owned())
+            }
+            #[cfg(feature = "protobuf")]
+            FrcValue::Protobuf(desc, _) => FrcTypeString::Proto(desc.type_str.clone()),
+        };
+        Some(type_string)
+    }
+}